@@ -1,4 +1,4 @@
-use std::iter;
+use std::{collections::VecDeque, iter};
 
 use crate::helpers::ascii_to_digit;
 
@@ -55,14 +55,7 @@ impl FoundDigit {
 
 #[aoc(day1, part1)]
 pub fn solve_part1(input: &str) -> u32 {
-    input
-        .lines()
-        .map(|line| {
-            let first = FoundDigit::first(line).unwrap();
-            let last = FoundDigit::last(line).unwrap();
-            (first.digit * 10) + last.digit
-        })
-        .sum()
+    sum_calibration_values(&Vocabulary::digits(), input)
 }
 
 struct NamedDigit {
@@ -132,4 +125,221 @@ pub fn solve_part2_search_all(input: &str) -> u32 {
         .sum()
 }
 
-// TODO: Find a faster way to implement part 2
+/// A trie of words with [Aho-Corasick](https://en.wikipedia.org/wiki/Aho%E2%80%93Corasick_algorithm) failure links,
+/// so that a single pass over some text finds the words ending at each index
+struct Automaton {
+    /// The trie child of each node for each byte, indexed by node then byte
+    ///
+    /// 0 means there is no child, as the root is never the child of another node
+    children: Vec<[u32; 256]>,
+    /// The number that the path to each node stands for, if it is a whole word
+    values: Vec<Option<u32>>,
+    /// Length of the path to each node
+    depths: Vec<usize>,
+    /// The node for the longest proper suffix of each node's path that is also a path in the trie
+    fail: Vec<u32>,
+    /// The length and value of the longest word that the path to each node ends with
+    longest: Vec<Option<(usize, u32)>>,
+    max_len: usize,
+}
+
+impl Automaton {
+    fn new() -> Self {
+        Automaton {
+            children: vec![[0; 256]],
+            values: vec![None],
+            depths: vec![0],
+            fail: vec![0],
+            longest: vec![None],
+            max_len: 0,
+        }
+    }
+
+    /// Add a word, replacing its value if it is already in the automaton
+    ///
+    /// The failure links are rebuilt for the whole trie each time, which is fine for the handful of words in a vocabulary
+    fn insert(&mut self, word: impl Iterator<Item = u8>, value: u32) {
+        let mut node = 0;
+        for byte in word {
+            node = match self.children[node][byte as usize] {
+                0 => {
+                    let child = self.children.len();
+                    self.children[node][byte as usize] = child as u32;
+                    self.children.push([0; 256]);
+                    self.values.push(None);
+                    self.depths.push(self.depths[node] + 1);
+                    child
+                }
+                child => child as usize,
+            };
+        }
+
+        self.values[node] = Some(value);
+        self.max_len = self.max_len.max(self.depths[node]);
+        self.link();
+    }
+
+    /// Build the failure links and longest words, visiting the nodes in order of depth so that each node's failure is built first
+    fn link(&mut self) {
+        self.fail = vec![0; self.children.len()];
+        self.longest = vec![None; self.children.len()];
+
+        let mut queue = VecDeque::from([0]);
+        while let Some(node) = queue.pop_front() {
+            for byte in 0..=u8::MAX {
+                let child = self.children[node][byte as usize] as usize;
+                if child == 0 {
+                    continue;
+                }
+
+                // The root's children can only fall back to the root
+                if node != 0 {
+                    self.fail[child] = self.step(self.fail[node] as usize, byte) as u32;
+                }
+                self.longest[child] = self.values[child]
+                    .map(|value| (self.depths[child], value))
+                    .or(self.longest[self.fail[child] as usize]);
+                queue.push_back(child);
+            }
+        }
+    }
+
+    /// The node reached by following `byte` from `node`, falling back along the failure links until some node has a child for it
+    fn step(&self, mut node: usize, byte: u8) -> usize {
+        loop {
+            match self.children[node][byte as usize] {
+                0 if node == 0 => return 0,
+                0 => node = self.fail[node] as usize,
+                child => return child as usize,
+            }
+        }
+    }
+}
+
+/// The words that can stand for a number in a calibration document, matched with automatons so that they can all be checked at once
+///
+/// Words can stand for numbers with more than one digit, the first digit of a line is the first digit of the first number in it,
+/// and the last digit is the last digit of the last number.
+/// Where words overlap, the one that starts first is the first number and the one that starts last is the last number,
+/// and if several words start at the same index the longest one is used.
+pub struct Vocabulary {
+    /// Matches the words, for reading the line forwards
+    forward: Automaton,
+    /// Matches the words reversed, for reading the line backwards
+    backward: Automaton,
+}
+
+impl Vocabulary {
+    pub fn new() -> Self {
+        Vocabulary {
+            forward: Automaton::new(),
+            backward: Automaton::new(),
+        }
+    }
+
+    /// Only the digits 0 to 9, as used by part 1
+    pub fn digits() -> Self {
+        (0..=9).map(|digit| (digit.to_string(), digit)).collect()
+    }
+
+    /// The digits, and the English names of the digits 1 to 9, as used by part 2
+    pub fn english() -> Self {
+        let mut vocabulary = Vocabulary::digits();
+        for named in &NUM_NAMES {
+            vocabulary.insert(named.name, named.digit);
+        }
+
+        vocabulary
+    }
+
+    /// Add a word, replacing its value if it is already in the vocabulary
+    pub fn insert(&mut self, word: &str, value: u32) {
+        assert!(!word.is_empty(), "Words can't be empty");
+
+        self.forward.insert(word.bytes(), value);
+        self.backward.insert(word.bytes().rev(), value);
+    }
+
+    /// Reads the line once from the start, so overlapping words like "eightwo" are both found
+    ///
+    /// The words are found in the order they end, so keep the one that starts first until no word ending later could start before it
+    fn first(&self, line: &str) -> Option<u32> {
+        let automaton = &self.forward;
+        let mut node = 0;
+        // Start and value of the first word so far
+        let mut first: Option<(usize, u32)> = None;
+        for (index, byte) in line.bytes().enumerate() {
+            if first.is_some_and(|(start, _)| index >= start + automaton.max_len) {
+                break;
+            }
+
+            node = automaton.step(node, byte);
+            if let Some((len, value)) = automaton.longest[node] {
+                // Of the words ending here the longest starts first, and a word ending later with the same start is longer
+                let start = index + 1 - len;
+                if first.is_none_or(|(first_start, _)| start <= first_start) {
+                    first = Some((start, value));
+                }
+            }
+        }
+
+        first.map(|(_, value)| value)
+    }
+
+    /// Reads the line once from the end, matching the reversed words
+    ///
+    /// The first reversed word found is the word that starts last, and the longest one found there is the longest word starting there
+    fn last(&self, line: &str) -> Option<u32> {
+        let automaton = &self.backward;
+        let mut node = 0;
+        line.bytes().rev().find_map(|byte| {
+            node = automaton.step(node, byte);
+            automaton.longest[node].map(|(_, value)| value)
+        })
+    }
+
+    /// The first and last digits of the line combined into a two digit number, or None if the line has no words
+    pub fn calibration_value(&self, line: &str) -> Option<u32> {
+        let mut first = self.first(line)?;
+        while first >= 10 {
+            first /= 10;
+        }
+        let last = self.last(line)? % 10;
+
+        Some((first * 10) + last)
+    }
+}
+
+impl Default for Vocabulary {
+    fn default() -> Self {
+        Vocabulary::new()
+    }
+}
+
+impl<S: AsRef<str>> FromIterator<(S, u32)> for Vocabulary {
+    fn from_iter<T: IntoIterator<Item = (S, u32)>>(words: T) -> Self {
+        let mut vocabulary = Vocabulary::new();
+        for (word, value) in words {
+            vocabulary.insert(word.as_ref(), value);
+        }
+
+        vocabulary
+    }
+}
+
+fn sum_calibration_values(vocabulary: &Vocabulary, input: &str) -> u32 {
+    input
+        .lines()
+        .map(|line| {
+            vocabulary
+                .calibration_value(line)
+                .unwrap_or_else(|| panic!("No digits in line: {}", line))
+        })
+        .sum()
+}
+
+/// Matches the digits and every name with automatons, reading forwards once for the first digit then backwards once for the last
+#[aoc(day1, part2, trie)]
+pub fn solve_part2_trie(input: &str) -> u32 {
+    sum_calibration_values(&Vocabulary::english(), input)
+}
@@ -48,7 +48,9 @@ pub fn input_gen(input: &str) -> Schematic {
     schematic
 }
 
-static VALUES_AROUND: [fn(&Vec2d<Value>, usize, usize) -> Option<&Value>; 8] = [
+type GetValue = fn(&Vec2d<Value>, usize, usize) -> Option<&Value>;
+
+static VALUES_AROUND: [GetValue; 8] = [
     Vec2d::above_left,
     Vec2d::above,
     Vec2d::above_right,
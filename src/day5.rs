@@ -1,91 +1,249 @@
-use crate::helpers::{parse_vec, RangeMap};
-
-// TODO: There are bound to be optimisations, part 2 takes 144 seconds to run!
-
-#[derive(Default)]
-pub struct Almanac {
-    seeds: Vec<u32>,
-    seed_to_soil: RangeMap<u32, u32>,
-    soil_to_fertiliser: RangeMap<u32, u32>,
-    fertiliser_to_water: RangeMap<u32, u32>,
-    water_to_light: RangeMap<u32, u32>,
-    light_to_temp: RangeMap<u32, u32>,
-    temp_to_humidity: RangeMap<u32, u32>,
-    humidity_to_location: RangeMap<u32, u32>,
+use std::{
+    collections::{hash_map::Entry, HashMap, VecDeque},
+    fmt::Debug,
+    iter,
+    ops::RangeInclusive,
+    str::FromStr,
+};
+
+use crate::helpers::{parse_vec, Integer, RangeMap, RangeSet};
+
+/// A map from the values of one category to the values of another, from a `<from>-to-<to> map:` section
+pub struct CategoryMap<T: Integer> {
+    from: String,
+    to: String,
+    map: RangeMap<T, T>,
+}
+
+pub struct Almanac<T: Integer = u64> {
+    seeds: Vec<T>,
+    /// In the order they appear in the input, which doesn't need to be the order they are applied
+    maps: Vec<CategoryMap<T>>,
+}
+
+/// The range of `len` values starting at `start`, or None if `len` is 0
+///
+/// Panics if the range would go past the maximum value of T
+fn range_from_len<T: Integer>(start: T, len: T) -> Option<RangeInclusive<T>> {
+    if len == T::ZERO {
+        return None;
+    }
+
+    let end = start
+        .checked_add(len - T::ONE)
+        .unwrap_or_else(|| panic!("Range of {} values starting at {} is too large", len, start));
+
+    Some(start..=end)
 }
 
 #[aoc_generator(day5)]
 pub fn input_gen(input: &str) -> Almanac {
+    parse_almanac(input)
+}
+
+pub fn parse_almanac<T>(input: &str) -> Almanac<T>
+where
+    T: Integer + FromStr,
+    T::Err: Debug,
+{
     let mut lines = input.lines();
 
     let (_, seeds) = lines.next().unwrap().split_once(": ").unwrap();
-
-    let mut almanac = Almanac::default();
-    almanac.seeds = parse_vec(seeds, " ");
-
-    lines.next(); // Skip first empty line
-
-    fn gen_map<'a>(lines: &mut impl Iterator<Item = &'a str>) -> RangeMap<u32, u32> {
-        lines.next(); // Skip map name line
-        RangeMap::from_iter(
+    let seeds = parse_vec(seeds, " ");
+
+    fn gen_map<'a, T>(header: &str, lines: &mut impl Iterator<Item = &'a str>) -> CategoryMap<T>
+    where
+        T: Integer + FromStr,
+        T::Err: Debug,
+    {
+        let (from, to) = header
+            .strip_suffix(" map:")
+            .and_then(|name| name.split_once("-to-"))
+            .unwrap_or_else(|| panic!("Invalid map header: {}", header));
+
+        let map = RangeMap::try_from_iter(
             lines
                 .by_ref()
-                .take_while(|line| line.len() > 0) // Take all lines until an empty line, and skip the empty line
-                .map(|line| {
+                .take_while(|line| !line.is_empty()) // Take all lines until an empty line, and skip the empty line
+                .filter_map(|line| {
                     let (dest_start, rest) = line.split_once(" ").unwrap();
                     let (source_start, len) = rest.split_once(" ").unwrap();
 
-                    let dest_start: u32 = dest_start.parse().unwrap();
-                    let source_start: u32 = source_start.parse().unwrap();
-                    let len: u32 = len.parse().unwrap();
+                    let dest_start: T = dest_start.parse().unwrap();
+                    let source_start: T = source_start.parse().unwrap();
+                    let len: T = len.parse().unwrap();
+
+                    // A range with a length of 0 doesn't map anything
+                    let source = range_from_len(source_start, len)?;
+
+                    // Mapping the end of the source range must not overflow
+                    if dest_start.checked_add(len - T::ONE).is_none() {
+                        panic!(
+                            "Destination range of {} values starting at {} is too large",
+                            len, dest_start
+                        );
+                    }
 
-                    (source_start..=(source_start + len - 1), dest_start)
+                    Some((source, dest_start))
                 }),
         )
+        .unwrap_or_else(|err| panic!("Invalid {}: {}", header, err));
+
+        CategoryMap {
+            from: from.to_string(),
+            to: to.to_string(),
+            map,
+        }
+    }
+
+    let mut maps = Vec::new();
+    while let Some(header) = lines.next() {
+        // Skips the empty line after the seeds, and any extra empty lines between maps
+        if !header.is_empty() {
+            maps.push(gen_map(header, &mut lines));
+        }
     }
 
-    // The order of calls to gen_map is important, do not rearrange
-    almanac.seed_to_soil = gen_map(&mut lines);
-    almanac.soil_to_fertiliser = gen_map(&mut lines);
-    almanac.fertiliser_to_water = gen_map(&mut lines);
-    almanac.water_to_light = gen_map(&mut lines);
-    almanac.light_to_temp = gen_map(&mut lines);
-    almanac.temp_to_humidity = gen_map(&mut lines);
-    almanac.humidity_to_location = gen_map(&mut lines);
+    Almanac { seeds, maps }
+}
 
-    almanac
+impl<T: Integer> Almanac<T> {
+    /// Find the shortest chain of maps that converts values of the `from` category into values of the `to` category
+    ///
+    /// Returns None if there is no way to get from one category to the other
+    pub fn path<'a>(&'a self, from: &'a str, to: &str) -> Option<Vec<&'a RangeMap<T, T>>> {
+        // The index of the map that was used to first reach each category
+        let mut reached_by: HashMap<&str, Option<usize>> = HashMap::from([(from, None)]);
+        let mut queue = VecDeque::from([from]);
+
+        while let Some(category) = queue.pop_front() {
+            if category == to {
+                let mut path = Vec::new();
+                let mut current = category;
+                while let Some(index) = reached_by[current] {
+                    path.push(&self.maps[index].map);
+                    current = &self.maps[index].from;
+                }
+
+                path.reverse();
+                return Some(path);
+            }
+
+            for (index, map) in self.maps.iter().enumerate() {
+                if map.from == category {
+                    if let Entry::Vacant(entry) = reached_by.entry(&map.to) {
+                        entry.insert(Some(index));
+                        queue.push_back(&map.to);
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Combine the chain of maps between two categories into a single map
+    pub fn map_between(&self, from: &str, to: &str) -> Option<RangeMap<T, T>> {
+        Some(
+            self.path(from, to)?
+                .into_iter()
+                .fold(RangeMap::new(), |combined, map| combined.then(map)),
+        )
+    }
+
+    /// All the maps, in the order a seed must be passed through them to get its location
+    fn stages(&self) -> Vec<&RangeMap<T, T>> {
+        self.path("seed", "location")
+            .expect("Almanac has no maps from seed to location")
+    }
+
+    /// The seed numbers of part 2, skipping any ranges with a length of 0
+    fn seed_ranges(&self) -> RangeSet<T> {
+        RangeSet::from_iter(
+            self.seeds
+                .chunks_exact(2)
+                .filter_map(|range| range_from_len(range[0], range[1])),
+        )
+    }
+
+    /// Combine all the maps into a single map from seed to location
+    fn seed_to_location(&self) -> RangeMap<T, T> {
+        self.map_between("seed", "location")
+            .expect("Almanac has no maps from seed to location")
+    }
 }
 
-fn find_lowest_location(almanac: &Almanac, seeds: impl Iterator<Item = u32>) -> u32 {
+fn find_lowest_location<T: Integer>(almanac: &Almanac<T>, seeds: impl Iterator<Item = T>) -> T {
+    let stages = almanac.stages();
     seeds
         .map(|seed| {
-            let soil = almanac.seed_to_soil.get_and_offset_or_key(seed);
-            let fertiliser = almanac.soil_to_fertiliser.get_and_offset_or_key(soil);
-            let water = almanac
-                .fertiliser_to_water
-                .get_and_offset_or_key(fertiliser);
-            let light = almanac.water_to_light.get_and_offset_or_key(water);
-            let temp = almanac.light_to_temp.get_and_offset_or_key(light);
-            let humidity = almanac.temp_to_humidity.get_and_offset_or_key(temp);
-            almanac.humidity_to_location.get_and_offset_or_key(humidity)
+            stages
+                .iter()
+                .fold(seed, |key, map| map.get_and_offset_or_key(key))
         })
         .min()
         .unwrap()
 }
 
 #[aoc(day5, part1)]
-pub fn solve_part1(almanac: &Almanac) -> u32 {
+pub fn solve_part1(almanac: &Almanac) -> u64 {
     find_lowest_location(almanac, almanac.seeds.iter().copied())
 }
 
+/// Looks up every seed in a single map that combines all the stages
+#[aoc(day5, part1, flattened)]
+pub fn solve_part1_flattened(almanac: &Almanac) -> u64 {
+    let seed_to_location = almanac.seed_to_location();
+    almanac
+        .seeds
+        .iter()
+        .map(|seed| seed_to_location.get_and_offset_or_key(*seed))
+        .min()
+        .unwrap()
+}
+
 #[aoc(day5, part2)]
-pub fn solve_part2(almanac: &Almanac) -> u32 {
-    let seeds = almanac.seeds.chunks_exact(2).flat_map(|range| {
-        let start = range[0];
-        let len = range[1];
+pub fn solve_part2(almanac: &Almanac) -> u64 {
+    almanac
+        .stages()
+        .iter()
+        .fold(almanac.seed_ranges(), |ranges, map| {
+            // Merging the mapped ranges keeps them from splitting into more and more pieces at each stage
+            RangeSet::from_iter(ranges.iter().flat_map(|range| map.map_range(range.clone())))
+        })
+        .iter()
+        .next()
+        .map(|range| *range.start())
+        .unwrap()
+}
 
-        start..(start + len)
-    });
+/// Searches upwards from the lowest location, stopping at the first one that comes from a seed
+#[aoc(day5, part2, reverse)]
+pub fn solve_part2_reverse(almanac: &Almanac) -> u64 {
+    let seed_to_location = almanac.seed_to_location();
+    let location_to_seed = seed_to_location
+        .inverse()
+        .unwrap_or_else(|err| panic!("Almanac can't be searched in reverse: {}", err));
+    let seed_ranges = almanac.seed_ranges();
+
+    // The pieces are in order of location, and the locations within a piece increase along with the seeds
+    location_to_seed
+        .map_range(0..=u64::MAX)
+        .into_iter()
+        .find_map(|seeds| {
+            seed_ranges
+                .intersection(&RangeSet::from_iter(iter::once(seeds)))
+                .iter()
+                .next()
+                .map(|planted| *planted.start())
+        })
+        .map(|seed| seed_to_location.get_and_offset_or_key(seed))
+        .unwrap()
+}
 
-    find_lowest_location(almanac, seeds)
+/// Checks every seed individually, very slow but useful for checking [solve_part2]
+#[aoc(day5, part2, brute_force)]
+pub fn solve_part2_brute_force(almanac: &Almanac) -> u64 {
+    let seeds = almanac.seed_ranges();
+    find_lowest_location(almanac, seeds.iter().cloned().flatten())
 }
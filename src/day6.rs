@@ -1,9 +1,101 @@
+use std::ops::RangeInclusive;
+
 #[derive(Default)]
 pub struct Race {
     time: u64,
     distance: u64,
 }
 
+/// How the boats move, the boats in the puzzle use [RaceModel::default]
+pub struct RaceModel {
+    /// Speed in mm/ms gained for each ms that the button is held
+    pub acceleration: u64,
+    /// Fastest speed in mm/ms that a boat can reach, however long the button is held
+    pub speed_cap: Option<u64>,
+    /// Time in ms between releasing the button and the boat starting to move
+    pub start_delay: u64,
+}
+
+impl Default for RaceModel {
+    fn default() -> Self {
+        RaceModel {
+            acceleration: 1,
+            speed_cap: None,
+            start_delay: 0,
+        }
+    }
+}
+
+impl RaceModel {
+    /// Distance in mm travelled in a race of `time` ms, if the button is held for `hold` ms
+    ///
+    /// Distances too large for u128 are all treated as u128::MAX
+    pub fn distance(&self, time: u64, hold: u64) -> u128 {
+        let moving = time.saturating_sub(hold).saturating_sub(self.start_delay);
+        let speed = u128::from(self.acceleration) * u128::from(hold);
+        let speed = self
+            .speed_cap
+            .map_or(speed, |cap| speed.min(u128::from(cap)));
+
+        speed.saturating_mul(u128::from(moving))
+    }
+}
+
+/// The first hold time in `holds` that `found` is true for, or None if there isn't one
+///
+/// `found` must be false for every hold time before the first one it's true for, and true for every one after
+fn first_hold(holds: RangeInclusive<u64>, found: impl Fn(u64) -> bool) -> Option<u64> {
+    let (mut low, mut high) = holds.into_inner();
+    if low > high || !found(high) {
+        return None;
+    }
+
+    // found(high) is always true
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if found(mid) {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+
+    Some(low)
+}
+
+impl Race {
+    /// The hold time that travels furthest, or the shortest one if several travel equally far
+    pub fn best_hold(&self, model: &RaceModel) -> u64 {
+        // Until the speed cap is reached the distance is a downwards facing parabola, after it the distance falls in a straight line
+        // So holding 1 ms longer keeps going further up to the best hold time, and never goes further after it
+        first_hold(0..=self.time, |hold| {
+            hold == self.time
+                || model.distance(self.time, hold + 1) <= model.distance(self.time, hold)
+        })
+        .unwrap()
+    }
+
+    /// Every hold time that beats the record, or None if none of them do
+    pub fn winning_holds(&self, model: &RaceModel) -> Option<RangeInclusive<u64>> {
+        let beats_record = |hold: u64| model.distance(self.time, hold) > u128::from(self.distance);
+
+        // The winning hold times are the ones around the best hold time
+        let best = self.best_hold(model);
+        let first = first_hold(0..=best, beats_record)?;
+        let last = first_hold(best..=self.time, |hold| {
+            hold == self.time || !beats_record(hold + 1)
+        })
+        .unwrap();
+
+        Some(first..=last)
+    }
+
+    pub fn count_ways_to_win(&self, model: &RaceModel) -> u64 {
+        self.winning_holds(model)
+            .map_or(0, |holds| holds.end() - holds.start() + 1)
+    }
+}
+
 #[aoc_generator(day6, part1)]
 pub fn input_gen1(input: &str) -> Vec<Race> {
     let mut rows = input.lines().map(|line| {
@@ -49,17 +141,38 @@ fn count_ways_to_win(race: &Race) -> u64 {
     // h₂ = floor( (t + i) / 2 )
     // Number of ways to win = h₂ - h₁ + 1
 
-    let t = race.time as f64;
-    let discriminator = t * t - 4.0 * ((race.distance + 1) as f64);
-    let sqrt_discriminator = discriminator.sqrt();
-
-    let hold_min = (t - sqrt_discriminator) / 2.0;
-    let hold_min = hold_min.ceil() as u64;
-
-    let hold_max = (t + sqrt_discriminator) / 2.0;
-    let hold_max = hold_max.floor() as u64;
+    // Floats can't represent every u64 exactly, so the intersections are found with integers instead
+    // t^2 can take up to 128 bits, and every hold time h with h(t - h) > record wins
+    // The parabola is symmetric around t/2, so h₂ = t - h₁
+    let t = u128::from(race.time);
+    let g = u128::from(race.distance) + 1;
+    let Some(discriminant) = (t * t).checked_sub(4 * g) else {
+        // The parabola never reaches the goal distance
+        return 0;
+    };
+
+    // Using floor(i) can put h₁ off by one, so move it to the exact boundary
+    let wins = |hold: u128| hold <= t && hold * (t - hold) >= g;
+    let mut hold_min = (t - discriminant.isqrt()) / 2;
+    while !wins(hold_min) {
+        if hold_min >= t / 2 {
+            return 0;
+        }
+        hold_min += 1;
+    }
+    while hold_min > 0 && wins(hold_min - 1) {
+        hold_min -= 1;
+    }
+
+    let hold_max = t - hold_min;
+    (hold_max - hold_min + 1) as u64
+}
 
-    hold_max - hold_min + 1
+/// Tries every hold time, only useful for checking [count_ways_to_win] on small races
+fn count_ways_to_win_brute_force(race: &Race) -> u64 {
+    (0..=race.time)
+        .filter(|hold| u128::from(*hold) * u128::from(race.time - hold) > u128::from(race.distance))
+        .count() as u64
 }
 
 #[aoc(day6, part1)]
@@ -67,6 +180,21 @@ pub fn solve_part1(races: &[Race]) -> u64 {
     races.iter().map(count_ways_to_win).product()
 }
 
+#[aoc(day6, part1, brute_force)]
+pub fn solve_part1_brute_force(races: &[Race]) -> u64 {
+    races.iter().map(count_ways_to_win_brute_force).product()
+}
+
+/// Searches for the winning hold times with [RaceModel::default], useful for checking [solve_part1]
+#[aoc(day6, part1, model)]
+pub fn solve_part1_model(races: &[Race]) -> u64 {
+    let model = RaceModel::default();
+    races
+        .iter()
+        .map(|race| race.count_ways_to_win(&model))
+        .product()
+}
+
 #[aoc_generator(day6, part2)]
 pub fn input_gen2(input: &str) -> Race {
     let mut rows = input.lines().map(|line| {
@@ -77,15 +205,26 @@ pub fn input_gen2(input: &str) -> Race {
             .unwrap()
     });
 
-    let mut race = Race::default();
     // The order of calls to next is important, do not rearrange
-    race.time = rows.next().unwrap();
-    race.distance = rows.next().unwrap();
+    let time = rows.next().unwrap();
+    let distance = rows.next().unwrap();
 
-    race
+    Race { time, distance }
 }
 
 #[aoc(day6, part2)]
 pub fn solve_part2(race: &Race) -> u64 {
     count_ways_to_win(race)
 }
+
+/// Searches for the winning hold times with [RaceModel::default], useful for checking [solve_part2]
+#[aoc(day6, part2, model)]
+pub fn solve_part2_model(race: &Race) -> u64 {
+    race.count_ways_to_win(&RaceModel::default())
+}
+
+/// Very slow for large races, but useful for checking [solve_part2]
+#[aoc(day6, part2, brute_force)]
+pub fn solve_part2_brute_force(race: &Race) -> u64 {
+    count_ways_to_win_brute_force(race)
+}
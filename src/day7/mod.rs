@@ -1,23 +1,15 @@
-macro_rules! impl_card_from_u8 {
-    () => {
-        impl From<u8> for Card {
-            fn from(value: u8) -> Self {
-                match value {
-                    b'2'..=b'9' => unsafe { std::mem::transmute(value - b'0') },
-                    b'T' => Card::T,
-                    b'J' => Card::Joker,
-                    b'Q' => Card::Queen,
-                    b'K' => Card::King,
-                    b'A' => Card::Ace,
-                    _ => panic!("Unknown card label: {}", value),
-                }
-            }
-        }
-    };
-}
+mod report;
+mod rules;
 
-mod part1;
-mod part2;
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    mem,
+    num::ParseIntError,
+};
+
+use report::{Comparison, Report};
+use rules::{Card, Hand, Rules};
 
 #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
 #[repr(u8)]
@@ -32,44 +24,209 @@ pub enum HandKind {
 }
 
 impl HandKind {
-    fn from_dup_counts(count1: u8, count2: Option<u8>) -> HandKind {
-        match (count1, count2) {
-            (5, _ /* None, 5 is max */) => HandKind::FiveOfAKind,
-            (4, _ /* None, 1 is filtered */) => HandKind::FourOfAKind,
-            (3, Some(2)) | (2, Some(3)) => HandKind::FullHouse,
-            (3, _ /* None, 1,1 is filtered */) => HandKind::ThreeOfAKind,
-            (2, Some(2)) => HandKind::TwoPair,
-            (2, _ /* None, 1,1,1 is filtered */) => HandKind::OnePair,
-            (1, _ /* None, 1,1,1,1 is filtered */) => HandKind::HighCard,
-            _ => panic!("Unexpected dup counts"),
+    /// Every kind, from weakest to strongest
+    const ALL: [HandKind; 7] = [
+        HandKind::HighCard,
+        HandKind::OnePair,
+        HandKind::TwoPair,
+        HandKind::ThreeOfAKind,
+        HandKind::FullHouse,
+        HandKind::FourOfAKind,
+        HandKind::FiveOfAKind,
+    ];
+
+    /// The kind of hand with these as its two largest groups of matching cards
+    ///
+    /// Hands with more than 5 cards count as the best 5 card hand in them, so six of a kind is five of a kind
+    fn from_group_sizes(largest: usize, second: usize) -> HandKind {
+        match (largest.min(5), second) {
+            (5, _) => HandKind::FiveOfAKind,
+            (4, _) => HandKind::FourOfAKind,
+            (3, 2..) => HandKind::FullHouse,
+            (3, _) => HandKind::ThreeOfAKind,
+            (2, 2..) => HandKind::TwoPair,
+            (2, _) => HandKind::OnePair,
+            _ => HandKind::HighCard,
         }
     }
 }
 
-pub trait Hand: Ord + Clone {
-    type Card: Ord + Copy + From<u8>;
+impl Display for HandKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            HandKind::HighCard => "High card",
+            HandKind::OnePair => "One pair",
+            HandKind::TwoPair => "Two pair",
+            HandKind::ThreeOfAKind => "Three of a kind",
+            HandKind::FullHouse => "Full house",
+            HandKind::FourOfAKind => "Four of a kind",
+            HandKind::FiveOfAKind => "Five of a kind",
+        };
+
+        // Pass the formatter on so that the name can be padded
+        f.pad(name)
+    }
+}
+
+/// Why a line of the input isn't a valid hand and bid
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    MissingBid,
+    InvalidBid(ParseIntError),
+    /// `column` starts from 1
+    UnknownLabel {
+        column: usize,
+        label: char,
+    },
+    WrongHandSize {
+        expected: usize,
+        found: usize,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// Starting from 1
+    line: usize,
+    kind: ParseErrorKind,
+}
 
-    fn new(cards: [Self::Card; 5]) -> Self;
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            ParseErrorKind::MissingBid => write!(f, "missing bid"),
+            ParseErrorKind::InvalidBid(err) => write!(f, "invalid bid, {}", err),
+            ParseErrorKind::UnknownLabel { column, label } => {
+                write!(f, "unknown card label {:?} in column {}", label, column)
+            }
+            ParseErrorKind::WrongHandSize { expected, found } => {
+                write!(f, "hand has {} cards, expected {}", found, expected)
+            }
+        }
+    }
 }
 
-fn parse_hand_bids<H: Hand>(input: &str) -> Vec<(H, u32)> {
+impl Error for ParseError {}
+
+/// Parse each line into a hand and its bid
+///
+/// Hands can have any number of cards, [Rules::hand] checks that they have the right number for the rules
+#[aoc_generator(day7)]
+pub fn input_gen(input: &str) -> Result<Vec<(Vec<Card>, u32)>, ParseError> {
     input
         .lines()
-        .map(|line| {
-            let (labels, bid) = line.split_once(" ").unwrap();
-            let bid: u32 = bid.parse().unwrap();
-            let labels: [u8; 5] = labels.as_bytes().try_into().unwrap();
-            (Hand::new(labels.map(H::Card::from)), bid)
+        .zip(1..)
+        .map(|(line_text, line)| {
+            let error = |kind| ParseError { line, kind };
+
+            let (labels, bid) = line_text
+                .split_once(' ')
+                .ok_or_else(|| error(ParseErrorKind::MissingBid))?;
+            let bid: u32 = bid
+                .parse()
+                .map_err(|err| error(ParseErrorKind::InvalidBid(err)))?;
+
+            let cards: Vec<Card> = labels
+                .chars()
+                .zip(1..)
+                .map(|(label, column)| {
+                    u8::try_from(label)
+                        .ok()
+                        .and_then(|label| Card::try_from(label).ok())
+                        .ok_or_else(|| error(ParseErrorKind::UnknownLabel { column, label }))
+                })
+                .collect::<Result<_, _>>()?;
+
+            Ok((cards, bid))
         })
         .collect()
 }
 
-fn sum_scaled_bids<H: Ord + Clone>(hand_bids: &[(H, u32)]) -> u32 {
-    let mut hand_bids = Vec::from(hand_bids);
-    hand_bids.sort_unstable_by(|(hand1, _), (hand2, _)| hand1.cmp(hand2));
-    hand_bids
+/// Sort by hand with a radix sort on the hands' keys, which takes linear time in the number of hands
+fn radix_sort<T: Copy + Default>(hand_bids: &mut Vec<(Hand, T)>) {
+    const DIGIT_BITS: u32 = 8;
+    const DIGITS: usize = 1 << DIGIT_BITS;
+
+    let mut sorted = vec![(Hand::default(), T::default()); hand_bids.len()];
+    for shift in (0..u32::BITS).step_by(DIGIT_BITS as usize) {
+        let digit = |(hand, _): &(Hand, T)| (hand.key() >> shift) as usize % DIGITS;
+
+        let mut starts = [0usize; DIGITS];
+        for hand_bid in hand_bids.iter() {
+            starts[digit(hand_bid)] += 1;
+        }
+
+        // Every hand has the same digit, often the case for unused card slots
+        if starts.contains(&hand_bids.len()) {
+            continue;
+        }
+
+        // Turn the counts into the index that each digit starts at
+        let mut start = 0;
+        for count in starts.iter_mut() {
+            (*count, start) = (start, start + *count);
+        }
+
+        // Hands with the same digit stay in the same order, keeping the order from the previous digits
+        for hand_bid in hand_bids.iter() {
+            let index = &mut starts[digit(hand_bid)];
+            sorted[*index] = *hand_bid;
+            *index += 1;
+        }
+
+        mem::swap(hand_bids, &mut sorted);
+    }
+}
+
+/// The hand for each of the cards when played with `rules`, adding the line number to any error
+fn hands(rules: &Rules, card_bids: &[(Vec<Card>, u32)]) -> Result<Vec<Hand>, ParseError> {
+    card_bids
+        .iter()
+        .zip(1..)
+        .map(|((cards, _), line)| rules.hand(cards).map_err(|kind| ParseError { line, kind }))
+        .collect()
+}
+
+/// Total winnings of every hand when played with `rules`
+fn total_winnings(rules: &Rules, card_bids: &[(Vec<Card>, u32)]) -> Result<u64, ParseError> {
+    let mut hand_bids: Vec<(Hand, u32)> = hands(rules, card_bids)?
+        .into_iter()
+        .zip(card_bids.iter().map(|(_, bid)| *bid))
+        .collect();
+
+    radix_sort(&mut hand_bids);
+    Ok(hand_bids
         .iter()
-        .enumerate()
-        .map(|(index, (_, bid))| *bid * (index as u32 + 1))
-        .sum()
+        .zip(1..)
+        .map(|((_, bid), rank)| u64::from(*bid) * rank)
+        .sum())
+}
+
+#[aoc(day7, part1)]
+pub fn solve_part1(card_bids: &[(Vec<Card>, u32)]) -> Result<u64, ParseError> {
+    total_winnings(&Rules::standard(), card_bids)
+}
+
+#[aoc(day7, part2)]
+pub fn solve_part2(card_bids: &[(Vec<Card>, u32)]) -> Result<u64, ParseError> {
+    total_winnings(&Rules::jokers(), card_bids)
+}
+
+/// Not a solution, outputs a table of how each hand scores with the part 1 rules
+#[aoc(day7, part1, report)]
+pub fn render_report_part1(card_bids: &[(Vec<Card>, u32)]) -> Result<String, ParseError> {
+    Ok(Report::new(&Rules::standard(), card_bids)?.to_string())
+}
+
+/// Not a solution, outputs a table of how each hand scores with the part 2 rules, including what the jokers act as
+#[aoc(day7, part2, report)]
+pub fn render_report_part2(card_bids: &[(Vec<Card>, u32)]) -> Result<String, ParseError> {
+    Ok(Report::new(&Rules::jokers(), card_bids)?.to_string())
+}
+
+/// Not a solution, outputs a table comparing how each hand scores with the part 1 and part 2 rules, marking the hands that differ
+#[aoc(day7, part2, compare)]
+pub fn render_comparison(card_bids: &[(Vec<Card>, u32)]) -> Result<String, ParseError> {
+    Ok(Comparison::new(&Rules::standard(), &Rules::jokers(), card_bids)?.to_string())
 }
@@ -0,0 +1,161 @@
+use std::fmt::{self, Display};
+
+use super::{
+    hands, radix_sort,
+    rules::{Card, Rules},
+    HandKind, ParseError,
+};
+
+/// How a single hand scored
+pub struct HandReport {
+    cards: Vec<Card>,
+    /// The cards with every wild card replaced by the card it acts as
+    best: Vec<Card>,
+    kind: HandKind,
+    /// Starting from 1 for the weakest hand
+    rank: usize,
+    bid: u32,
+}
+
+impl HandReport {
+    pub fn winnings(&self) -> u64 {
+        u64::from(self.bid) * self.rank as u64
+    }
+}
+
+/// How every hand scored with a set of rules, displayed as a table in the order of the input
+pub struct Report {
+    hands: Vec<HandReport>,
+}
+
+impl Report {
+    /// Fails if any hand has the wrong number of cards for the rules
+    pub fn new(rules: &Rules, card_bids: &[(Vec<Card>, u32)]) -> Result<Self, ParseError> {
+        let hands = hands(rules, card_bids)?;
+
+        // Sort the index of each hand to find its rank
+        let mut ranked: Vec<_> = hands.iter().copied().zip(0..).collect();
+        radix_sort(&mut ranked);
+
+        let mut ranks = vec![0; card_bids.len()];
+        for (rank, (_, index)) in (1..).zip(&ranked) {
+            ranks[*index] = rank;
+        }
+
+        let hands = card_bids
+            .iter()
+            .zip(hands)
+            .zip(ranks)
+            .map(|(((cards, bid), hand), rank)| HandReport {
+                cards: cards.clone(),
+                best: rules.best_substitute(cards),
+                kind: hand.kind(),
+                rank,
+                bid: *bid,
+            })
+            .collect();
+
+        Ok(Report { hands })
+    }
+
+    pub fn total_winnings(&self) -> u64 {
+        self.hands.iter().map(HandReport::winnings).sum()
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let labels = |cards: &[Card]| cards.iter().map(|card| card.label()).collect::<String>();
+
+        let hand_width = self
+            .hands
+            .iter()
+            .map(|hand| hand.cards.len())
+            .max()
+            .unwrap_or(0)
+            .max("Hand".len());
+
+        writeln!(
+            f,
+            "{:<hand_width$}  {:<hand_width$}  {:<15}  {:>8}  {:>8}  {:>12}",
+            "Hand", "Best", "Kind", "Rank", "Bid", "Winnings"
+        )?;
+        for hand in &self.hands {
+            writeln!(
+                f,
+                "{:<hand_width$}  {:<hand_width$}  {:<15}  {:>8}  {:>8}  {:>12}",
+                labels(&hand.cards),
+                labels(&hand.best),
+                hand.kind,
+                hand.rank,
+                hand.bid,
+                hand.winnings()
+            )?;
+        }
+        write!(f, "Total winnings: {}", self.total_winnings())
+    }
+}
+
+/// How every hand scored with two sets of rules side by side, displayed as a table in the order of the input
+///
+/// Rows where the hand's kind or rank differs between the rules are marked with a `*`, to make the disagreements easy to find
+pub struct Comparison {
+    first: Report,
+    second: Report,
+}
+
+impl Comparison {
+    /// Fails if any hand has the wrong number of cards for either set of rules
+    pub fn new(
+        first: &Rules,
+        second: &Rules,
+        card_bids: &[(Vec<Card>, u32)],
+    ) -> Result<Self, ParseError> {
+        Ok(Comparison {
+            first: Report::new(first, card_bids)?,
+            second: Report::new(second, card_bids)?,
+        })
+    }
+}
+
+impl Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let labels = |cards: &[Card]| cards.iter().map(|card| card.label()).collect::<String>();
+
+        let hand_width = self
+            .first
+            .hands
+            .iter()
+            .map(|hand| hand.cards.len())
+            .max()
+            .unwrap_or(0)
+            .max("Best 2".len());
+
+        writeln!(
+            f,
+            "   {:<hand_width$}  {:<15}  {:>8}  {:<hand_width$}  {:<15}  {:>8}  {:>8}",
+            "Hand", "Kind 1", "Rank 1", "Best 2", "Kind 2", "Rank 2", "Bid"
+        )?;
+        for (first, second) in self.first.hands.iter().zip(&self.second.hands) {
+            let differs = first.kind != second.kind || first.rank != second.rank;
+            writeln!(
+                f,
+                "{}  {:<hand_width$}  {:<15}  {:>8}  {:<hand_width$}  {:<15}  {:>8}  {:>8}",
+                if differs { '*' } else { ' ' },
+                labels(&first.cards),
+                first.kind,
+                first.rank,
+                labels(&second.best),
+                second.kind,
+                second.rank,
+                first.bid
+            )?;
+        }
+        write!(
+            f,
+            "Total winnings: {} then {}",
+            self.first.total_winnings(),
+            self.second.total_winnings()
+        )
+    }
+}
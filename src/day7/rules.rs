@@ -0,0 +1,209 @@
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
+
+use super::{HandKind, ParseErrorKind};
+
+/// A single card label, how strong each card is depends on the [Rules]
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Card {
+    Two,
+    Three,
+    Four,
+    Five,
+    Six,
+    Seven,
+    Eight,
+    Nine,
+    T, // No clue what card this label represents
+    Joker,
+    Queen,
+    King,
+    Ace,
+}
+
+impl Card {
+    /// Every card, in the order of their labels in the standard rules
+    pub const ALL: [Card; 13] = [
+        Card::Two,
+        Card::Three,
+        Card::Four,
+        Card::Five,
+        Card::Six,
+        Card::Seven,
+        Card::Eight,
+        Card::Nine,
+        Card::T,
+        Card::Joker,
+        Card::Queen,
+        Card::King,
+        Card::Ace,
+    ];
+
+    pub fn label(self) -> char {
+        char::from(b"23456789TJQKA"[self as usize])
+    }
+}
+
+/// A byte that isn't the label of any card
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnknownLabel(pub u8);
+
+impl Display for UnknownLabel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown card label {:?}", char::from(self.0))
+    }
+}
+
+impl Error for UnknownLabel {}
+
+impl TryFrom<u8> for Card {
+    type Error = UnknownLabel;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            b'2'..=b'9' => Ok(Card::ALL[(value - b'2') as usize]),
+            b'T' => Ok(Card::T),
+            b'J' => Ok(Card::Joker),
+            b'Q' => Ok(Card::Queen),
+            b'K' => Ok(Card::King),
+            b'A' => Ok(Card::Ace),
+            _ => Err(UnknownLabel(value)),
+        }
+    }
+}
+
+/// A variant of Camel Cards: how strong each card is, which cards are wild, and how many cards are in a hand
+pub struct Rules {
+    /// Strength of each card when breaking ties, indexed by `Card as usize`
+    strengths: [u8; 13],
+    /// Whether each card is wild, indexed by `Card as usize`
+    wild: [bool; 13],
+    hand_size: usize,
+}
+
+impl Rules {
+    /// `order` must list every card once, from weakest to strongest
+    ///
+    /// Wild cards act as whichever card makes the best kind of hand, but keep their own strength when breaking ties
+    pub fn new(order: [Card; 13], wild: &[Card], hand_size: usize) -> Self {
+        assert!(hand_size > 0, "Hands must have at least one card");
+        assert!(
+            hand_size <= Hand::MAX_CARDS,
+            "Hands can't have more than {} cards",
+            Hand::MAX_CARDS
+        );
+
+        let mut strengths = [None; 13];
+        for (strength, card) in order.into_iter().enumerate() {
+            assert!(
+                strengths[card as usize].replace(strength as u8).is_none(),
+                "{:?} is in the card order more than once",
+                card
+            );
+        }
+
+        let mut is_wild = [false; 13];
+        for card in wild {
+            is_wild[*card as usize] = true;
+        }
+
+        Rules {
+            // Each card is in the order once, so every card must have a strength
+            strengths: strengths.map(Option::unwrap),
+            wild: is_wild,
+            hand_size,
+        }
+    }
+
+    /// The rules for part 1
+    pub fn standard() -> Self {
+        Rules::new(Card::ALL, &[], 5)
+    }
+
+    /// The rules for part 2, where jokers are wild and the weakest card
+    pub fn jokers() -> Self {
+        let mut order = Card::ALL;
+        // Move the joker to the start, keeping the order of the others
+        order[..=Card::Joker as usize].rotate_right(1);
+
+        Rules::new(order, &[Card::Joker], 5)
+    }
+
+    /// The cards with every wild card replaced by the card it acts as in the best kind of hand
+    pub fn best_substitute(&self, cards: &[Card]) -> Vec<Card> {
+        let mut counts = [0usize; 13];
+        for card in cards {
+            if !self.wild[*card as usize] {
+                counts[*card as usize] += 1;
+            }
+        }
+
+        // Like in [Rules::hand] the wild cards join the largest group, picking the strongest card if groups are the same size
+        // A hand of only wild cards becomes the strongest card that isn't wild
+        let substitute = Card::ALL
+            .into_iter()
+            .filter(|card| !self.wild[*card as usize])
+            .max_by_key(|card| (counts[*card as usize], self.strengths[*card as usize]));
+
+        cards
+            .iter()
+            .map(|card| match substitute {
+                Some(substitute) if self.wild[*card as usize] => substitute,
+                _ => *card,
+            })
+            .collect()
+    }
+
+    /// Fails with [ParseErrorKind::WrongHandSize] if the rules are for a different number of cards
+    pub fn hand(&self, cards: &[Card]) -> Result<Hand, ParseErrorKind> {
+        if cards.len() != self.hand_size {
+            return Err(ParseErrorKind::WrongHandSize {
+                expected: self.hand_size,
+                found: cards.len(),
+            });
+        }
+
+        let mut counts = [0usize; 13];
+        let mut wild_count = 0;
+        for card in cards {
+            if self.wild[*card as usize] {
+                wild_count += 1;
+            } else {
+                counts[*card as usize] += 1;
+            }
+        }
+
+        // Wild cards always make the best hand by joining the largest group, which also covers a hand of only wild cards
+        counts.sort_unstable_by(|count1, count2| count2.cmp(count1));
+        let kind = HandKind::from_group_sizes(counts[0] + wild_count, counts[1]);
+
+        let key = cards.iter().zip((0..Hand::MAX_CARDS as u32).rev()).fold(
+            (kind as u32) << (Hand::MAX_CARDS * 4),
+            |key, (card, slot)| key | u32::from(self.strengths[*card as usize]) << (slot * 4),
+        );
+
+        Ok(Hand(key))
+    }
+}
+
+/// A hand packed into a single sort key, so that comparing the keys compares the hands
+///
+/// The top 4 bits are the kind, then each card's strength takes 4 bits, starting with the first card dealt.
+/// Any unused slots at the bottom are 0
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Default)]
+pub struct Hand(u32);
+
+impl Hand {
+    /// Number of card slots below the kind
+    const MAX_CARDS: usize = 7;
+
+    pub fn key(self) -> u32 {
+        self.0
+    }
+
+    pub fn kind(self) -> HandKind {
+        HandKind::ALL[(self.0 >> (Hand::MAX_CARDS * 4)) as usize]
+    }
+}
@@ -1,35 +1,283 @@
-use std::collections::HashMap;
+use std::fmt::Write;
 
-use crate::helpers::{get_bytes_len, lcm};
+use crate::helpers::{checked_lcm_all, crt, get_bytes_len, Interner};
 
 type NodeName = [u8; 3];
 
+/// Dense index of a node, in the order the nodes are listed in the input
+type NodeId = u16;
+
 #[derive(Default)]
 pub struct Map {
     /// true means left, false means right
     steps: Vec<bool>,
-    nodes: HashMap<NodeName, (NodeName, NodeName)>,
+    names: Interner<NodeName, NodeId>,
+    /// The left and right nodes of each node, indexed by NodeId
+    nodes: Vec<(NodeId, NodeId)>,
+    /// The node reached from each node by following all of the steps once, indexed by NodeId
+    full_steps: Vec<NodeId>,
+}
+
+/// The steps at which a single ghost is on an end node, found by following its walk until it repeats
+///
+/// The walk repeats once it gets back to the same node at the same point in the instructions
+struct GhostCycle {
+    /// Number of steps before the walk enters the cycle
+    prefix: usize,
+    /// Number of steps to go around the cycle once
+    len: usize,
+    /// Steps before entering the cycle at which the ghost is on an end node
+    prefix_hits: Vec<usize>,
+    /// Steps during the first time around the cycle at which the ghost is on an end node, these repeat every `len` steps
+    cycle_hits: Vec<usize>,
+}
+
+impl GhostCycle {
+    fn is_end_at(&self, step: usize) -> bool {
+        if step < self.prefix {
+            self.prefix_hits.contains(&step)
+        } else {
+            let offset = (step - self.prefix) % self.len;
+            self.cycle_hits.contains(&(self.prefix + offset))
+        }
+    }
 }
 
 impl Map {
-    fn steps_between(&self, start_id: [u8; 3], stop: impl Fn([u8; 3]) -> bool) -> usize {
-        let mut node = self.nodes[&start_id];
+    fn next(&self, id: NodeId, take_left: bool) -> NodeId {
+        let (left, right) = self.nodes[id as usize];
+        if take_left {
+            left
+        } else {
+            right
+        }
+    }
+
+    fn steps_between(&self, start_id: NodeId, stop: impl Fn(NodeId) -> bool) -> usize {
+        let mut id = start_id;
         self.steps
             .iter()
             .copied()
             .cycle()
             .take_while(|take_left| {
-                let id = if *take_left { node.0 } else { node.1 };
-                if stop(id) {
-                    false
-                } else {
-                    node = self.nodes[&id];
-                    true
-                }
+                id = self.next(id, *take_left);
+                !stop(id)
             })
             .count()
             + 1
     }
+
+    fn ghost_cycle(&self, start_id: NodeId, is_end: impl Fn(NodeId) -> bool) -> GhostCycle {
+        // The walk repeats as soon as a node repeats at the start of the steps, so only those need to be checked
+        // Index of the first time around the steps that each node was seen at the start of
+        let mut seen: Vec<Option<usize>> = vec![None; self.nodes.len()];
+        let mut id = start_id;
+        let (prefix_rounds, cycle_rounds) = (0..)
+            .find_map(|round| {
+                if let Some(first) = seen[id as usize] {
+                    return Some((first, round - first));
+                }
+
+                seen[id as usize] = Some(round);
+                id = self.full_steps[id as usize];
+                None
+            })
+            .unwrap();
+
+        let prefix = prefix_rounds * self.steps.len();
+        let len = cycle_rounds * self.steps.len();
+
+        let mut id = start_id;
+        let (prefix_hits, cycle_hits) = self
+            .steps
+            .iter()
+            .copied()
+            .cycle()
+            .take(prefix + len)
+            .enumerate()
+            .filter_map(|(step, take_left)| {
+                let hit = is_end(id).then_some(step);
+                id = self.next(id, take_left);
+                hit
+            })
+            .partition(|hit| *hit < prefix);
+
+        GhostCycle {
+            prefix,
+            len,
+            prefix_hits,
+            cycle_hits,
+        }
+    }
+
+    /// Render the network as a [Graphviz](https://graphviz.org/) DOT graph
+    ///
+    /// Start nodes are filled green and end nodes red, when `show_cycles` is set the edges of each ghost's cycle get their own colour
+    fn to_dot(&self, show_cycles: bool) -> String {
+        const CYCLE_COLOURS: [&str; 6] = ["blue", "orange", "purple", "cyan4", "magenta", "gold3"];
+
+        // The ghosts whose cycles use each edge, indexed by NodeId then by 0 for left, 1 for right
+        let mut edge_ghosts: Vec<[Vec<usize>; 2]> = vec![Default::default(); self.nodes.len()];
+        if show_cycles {
+            for (ghost, start_id) in self.ids_ending_with(b'A').enumerate() {
+                let cycle = self.ghost_cycle(start_id, |_| false);
+                let mut id = start_id;
+                for (step, take_left) in self
+                    .steps
+                    .iter()
+                    .cycle()
+                    .take(cycle.prefix + cycle.len)
+                    .enumerate()
+                {
+                    if step >= cycle.prefix {
+                        let ghosts = &mut edge_ghosts[id as usize][usize::from(!take_left)];
+                        if !ghosts.contains(&ghost) {
+                            ghosts.push(ghost);
+                        }
+                    }
+                    id = self.next(id, *take_left);
+                }
+            }
+        }
+
+        let name = |id: NodeId| String::from_utf8_lossy(self.names.value(id)).into_owned();
+
+        let mut dot = String::from("digraph day8 {\n");
+        for (id, node_name) in self.names.iter() {
+            let fill = match node_name[2] {
+                b'A' => Some("palegreen"),
+                b'Z' => Some("lightcoral"),
+                _ => None,
+            };
+            if let Some(fill) = fill {
+                writeln!(
+                    dot,
+                    "    \"{}\" [style=filled, fillcolor={}];",
+                    name(id),
+                    fill
+                )
+                .unwrap();
+            }
+        }
+
+        for (index, ((left, right), [left_ghosts, right_ghosts])) in
+            self.nodes.iter().zip(&edge_ghosts).enumerate()
+        {
+            // Draw a single edge when both steps go to the same node, unless they are in different cycles
+            let edges = if left == right && left_ghosts == right_ghosts {
+                vec![(*left, "LR", left_ghosts)]
+            } else {
+                vec![(*left, "L", left_ghosts), (*right, "R", right_ghosts)]
+            };
+
+            let id = index as NodeId;
+            for (to, label, ghosts) in edges {
+                write!(
+                    dot,
+                    "    \"{}\" -> \"{}\" [label=\"{}\"",
+                    name(id),
+                    name(to),
+                    label
+                )
+                .unwrap();
+                if !ghosts.is_empty() {
+                    let colours: Vec<&str> = ghosts
+                        .iter()
+                        .map(|ghost| CYCLE_COLOURS[ghost % CYCLE_COLOURS.len()])
+                        .collect();
+                    write!(dot, ", color=\"{}\", penwidth=2", colours.join(":")).unwrap();
+                }
+                dot.push_str("];\n");
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// The ids of every node with a name that ends with `last`
+    fn ids_ending_with(&self, last: u8) -> impl Iterator<Item = NodeId> + '_ {
+        self.names
+            .iter()
+            .filter(move |(_, name)| name[2] == last)
+            .map(|(id, _)| id)
+    }
+}
+
+/// Most combinations of congruences to build up before switching to stepping through the candidates
+const MAX_CONGRUENCES: usize = 1 << 16;
+
+/// Find the first step at which every ghost is on an end node at the same time
+///
+/// Combining every ghost's cycle hits with the Chinese remainder theorem needs one congruence per combination of hits,
+/// which is the product of the numbers of hits and can be far too many to store.
+/// So the ghosts with the fewest hits are combined first, and once the next ghost would take the combinations past [MAX_CONGRUENCES]
+/// the rest are checked by stepping through the combinations found so far.
+/// In the worst case that steps through every combination up to the least common multiple of the cycle lengths
+fn first_common_end(ghosts: &[GhostCycle]) -> Option<usize> {
+    // Before every ghost has entered its cycle, just check each step
+    // The ghosts have to take at least one step before they can stop
+    let all_in_cycle = ghosts.iter().map(|ghost| ghost.prefix).max()?.max(1);
+    if let Some(step) =
+        (1..all_in_cycle).find(|step| ghosts.iter().all(|ghost| ghost.is_end_at(*step)))
+    {
+        return Some(step);
+    }
+
+    let mut ghosts: Vec<&GhostCycle> = ghosts.iter().collect();
+    ghosts.sort_unstable_by_key(|ghost| ghost.cycle_hits.len());
+
+    // After that every ghost is on an end node at step t exactly when t ≡ hit (mod len) for one of its cycle hits
+    // Combine the possible congruences for each ghost in turn, keeping each combination that has a solution
+    // Every combination uses the same ghosts, so they all share the same modulus
+    let mut congruences: Vec<(i128, i128)> = vec![(0, 1)];
+    let mut combined = 0;
+    for ghost in &ghosts {
+        if combined > 0 && congruences.len() * ghost.cycle_hits.len() > MAX_CONGRUENCES {
+            break;
+        }
+
+        congruences = congruences
+            .iter()
+            .flat_map(|congruence| {
+                ghost
+                    .cycle_hits
+                    .iter()
+                    .filter_map(|hit| crt([*congruence, (*hit as i128, ghost.len as i128)]))
+            })
+            .collect();
+        congruences.sort_unstable();
+        congruences.dedup();
+        combined += 1;
+    }
+
+    // Move each solution up to the first one that is after every ghost has entered its cycle
+    let all_in_cycle = all_in_cycle as i128;
+    let mut starts: Vec<i128> = congruences
+        .iter()
+        .map(|(step, modulus)| {
+            if *step < all_in_cycle {
+                step + (all_in_cycle - step + modulus - 1) / modulus * modulus
+            } else {
+                *step
+            }
+        })
+        .collect();
+    starts.sort_unstable();
+
+    let remaining = &ghosts[combined..];
+    if remaining.is_empty() {
+        return starts.first().and_then(|step| usize::try_from(*step).ok());
+    }
+
+    // Every ghost is back where it started after the least common multiple of the cycle lengths,
+    // so if there is no common end before then there never is one
+    let &(_, modulus) = congruences.first()?;
+    let period = checked_lcm_all(ghosts.iter().map(|ghost| ghost.len as i128)).unwrap_or(i128::MAX);
+    (0..period / modulus)
+        .flat_map(|round| starts.iter().map(move |start| start + round * modulus))
+        .map_while(|step| usize::try_from(step).ok())
+        .find(|step| remaining.iter().all(|ghost| ghost.is_end_at(*step)))
 }
 
 #[aoc_generator(day8)]
@@ -48,29 +296,70 @@ pub fn input_gen(input: &str) -> Map {
 
     lines.next(); // Ignore empty line
 
-    map.nodes = HashMap::from_iter(lines.map(|line| {
-        let id = get_bytes_len::<0, 3>(line);
-        let left = get_bytes_len::<{ b"AAA = (".len() }, 3>(line);
-        let right = get_bytes_len::<{ b"AAA = (AAA, ".len() }, 3>(line);
+    let lines: Vec<&str> = lines.collect();
 
-        (id, (left, right))
-    }));
+    // Intern all the names first, so that the ids are in the same order as the lines
+    for line in &lines {
+        map.names.intern(get_bytes_len::<0, 3>(line));
+    }
+
+    map.nodes = lines
+        .iter()
+        .map(|line| {
+            let left = get_bytes_len::<{ b"AAA = (".len() }, 3>(line);
+            let right = get_bytes_len::<{ b"AAA = (AAA, ".len() }, 3>(line);
+
+            (map.names.intern(left), map.names.intern(right))
+        })
+        .collect();
+
+    map.full_steps = (0..map.nodes.len())
+        .map(|id| {
+            map.steps
+                .iter()
+                .fold(id as NodeId, |id, take_left| map.next(id, *take_left))
+        })
+        .collect();
 
     map
 }
 
 #[aoc(day8, part1)]
 pub fn solve_part1(map: &Map) -> usize {
-    map.steps_between(*b"AAA", |id| id == *b"ZZZ")
+    let start = map.names.get(b"AAA").unwrap();
+    let end = map.names.get(b"ZZZ").unwrap();
+    map.steps_between(start, |id| id == end)
 }
 
 #[aoc(day8, part2)]
 pub fn solve_part2(map: &Map) -> usize {
+    let ghosts: Vec<GhostCycle> = map
+        .ids_ending_with(b'A')
+        .map(|start_id| map.ghost_cycle(start_id, |id| map.names.value(id)[2] == b'Z'))
+        .collect();
+
+    first_common_end(&ghosts).expect("The ghosts are never all on end nodes at the same time")
+}
+
+/// Only works if each ghost loops back to a single end node, after as many steps as it took to first reach it
+#[aoc(day8, part2, lcm)]
+pub fn solve_part2_lcm(map: &Map) -> usize {
     // Input data always forms loops that only reach a single end node, so we only need to calculate the length of each cycle, then find the least common multiple
-    map.nodes
-        .iter()
-        .filter_map(|(id, _)| if id[2] == b'A' { Some(*id) } else { None })
-        .map(|start_id| map.steps_between(start_id, |id| id[2] == b'Z'))
-        .reduce(lcm)
-        .unwrap()
+    checked_lcm_all(
+        map.ids_ending_with(b'A')
+            .map(|start_id| map.steps_between(start_id, |id| map.names.value(id)[2] == b'Z')),
+    )
+    .expect("Number of steps is too large for usize")
+}
+
+/// Not a solution, outputs the network as a DOT graph with only the start and end nodes marked, for checking the inputs by eye
+#[aoc(day8, part1, dot)]
+pub fn render_dot_part1(map: &Map) -> String {
+    map.to_dot(false)
+}
+
+/// Not a solution, outputs the network as a DOT graph with each ghost's cycle coloured, for checking the inputs by eye
+#[aoc(day8, part2, dot)]
+pub fn render_dot_part2(map: &Map) -> String {
+    map.to_dot(true)
 }
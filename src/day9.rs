@@ -1,62 +1,237 @@
-use crate::helpers::parse_vec;
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    num::ParseIntError,
+};
+
+use crate::helpers::polynomial::{FitError, Polynomial};
+
+/// Why a line of the input can't be extrapolated
+#[derive(Debug, PartialEq, Eq)]
+pub enum SequenceErrorKind {
+    InvalidNumber(ParseIntError),
+    /// The line has fewer than 2 values, so there are no differences to extrapolate with
+    NeverSettles,
+    /// A difference or the extrapolated value doesn't fit in the integer type
+    Overflow,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct SequenceError {
+    /// Starting from 1
+    line: usize,
+    kind: SequenceErrorKind,
+}
+
+impl Display for SequenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            SequenceErrorKind::InvalidNumber(err) => write!(f, "invalid number, {}", err),
+            SequenceErrorKind::NeverSettles => write!(f, "the differences never become constant"),
+            SequenceErrorKind::Overflow => write!(f, "the values are too large"),
+        }
+    }
+}
+
+impl Error for SequenceError {}
 
 #[aoc_generator(day9)]
-pub fn input_gen(input: &str) -> Vec<Vec<i32>> {
+pub fn input_gen(input: &str) -> Result<Vec<Vec<i64>>, SequenceError> {
     input
         .lines()
-        .map(|line| parse_vec::<i32>(line, " "))
+        .zip(1..)
+        .map(|(line_text, line)| {
+            line_text
+                .split_ascii_whitespace()
+                .map(str::parse)
+                .collect::<Result<_, _>>()
+                .map_err(|err| SequenceError {
+                    line,
+                    kind: SequenceErrorKind::InvalidNumber(err),
+                })
+        })
         .collect()
 }
 
-fn get_diffs<'a>(changes: &'a Vec<i32>) -> impl Iterator<Item = i32> + 'a {
-    changes.windows(2).map(|values| values[1] - values[0])
+/// Sum the result of `value` for each line, adding the line number to any error
+fn sum_lines(
+    values_changes: &[Vec<i64>],
+    value: impl Fn(&[i64]) -> Result<i64, SequenceErrorKind>,
+) -> Result<i64, SequenceError> {
+    values_changes
+        .iter()
+        .zip(1..)
+        .try_fold(0i64, |sum, (values, line)| {
+            value(values)
+                .and_then(|value| sum.checked_add(value).ok_or(SequenceErrorKind::Overflow))
+                .map_err(|kind| SequenceError { line, kind })
+        })
 }
 
-fn all_eq(diffs: &Vec<i32>) -> bool {
+fn get_diffs(changes: &[i128]) -> Option<Vec<i128>> {
+    changes
+        .windows(2)
+        .map(|values| values[1].checked_sub(values[0]))
+        .collect()
+}
+
+fn all_eq(diffs: &[i128]) -> bool {
     diffs.windows(2).all(|values| values[0] == values[1])
 }
 
-fn each_diffs(changes: &Vec<i32>, mut callback: impl FnMut(&Vec<i32>) -> ()) {
-    let mut diffs: &mut Vec<i32> = &mut get_diffs(changes).collect();
-    callback(diffs);
+/// Call `callback` with the values, then each layer of differences, until a layer is constant
+///
+/// A layer with a single value counts as constant, so this always settles once there are at least 2 values
+fn each_diffs(changes: &[i64], mut callback: impl FnMut(&[i128])) -> Result<(), SequenceErrorKind> {
+    if changes.len() < 2 {
+        return Err(SequenceErrorKind::NeverSettles);
+    }
+
+    let mut diffs: Vec<i128> = changes.iter().copied().map(i128::from).collect();
+    loop {
+        callback(&diffs);
+        if all_eq(&diffs) {
+            return Ok(());
+        }
+
+        diffs = get_diffs(&diffs).ok_or(SequenceErrorKind::Overflow)?;
+    }
+}
+
+/// Find the value at `index` of the sequence, treating `values` as the sequence at indices 0, 1, 2, ...
+///
+/// The sequence is the polynomial that passes through all the values, which is what repeatedly taking differences finds.
+/// Each value gets a weight from the Lagrange form for equally spaced points:
+/// `(-1)^(n-1-i) * C(index, i) * C(index-i-1, n-1-i)` for value `i` of `n`.
+/// The binomial coefficients are updated as the values are visited, so this only needs one pass over the values.
+/// Returns None if any part of the calculation overflows
+pub fn value_at(values: &[i64], index: i64) -> Option<i64> {
+    value_at_wide(values, index)?.try_into().ok()
+}
+
+/// [value_at], without limiting the result to i64
+fn value_at_wide(values: &[i64], index: i64) -> Option<i128> {
+    let n = values.len() as i128;
+    let index = i128::from(index);
+    if (0..n).contains(&index) {
+        return Some(values[index as usize].into());
+    }
 
-    if !all_eq(diffs) {
-        let mut next_diffs: &mut Vec<i32> = &mut get_diffs(diffs).collect();
-        callback(next_diffs);
+    // Outside the values, index-i-1 is never 0 while it is being divided by
+    let mut below: i128 = 1; // C(index, i)
+    let mut above = binomial(index - 1, n - 1)?; // C(index-i-1, n-1-i)
+    let mut total: i128 = 0;
 
-        while !all_eq(next_diffs) {
-            std::mem::swap(&mut diffs, &mut next_diffs);
-            next_diffs.clear();
-            next_diffs.extend(get_diffs(diffs));
-            callback(next_diffs);
+    for (i, value) in values.iter().enumerate() {
+        let i = i as i128;
+        let remaining = n - 1 - i;
+
+        let term = below.checked_mul(above)?.checked_mul(i128::from(*value))?;
+        total = if remaining % 2 == 0 {
+            total.checked_add(term)?
+        } else {
+            total.checked_sub(term)?
+        };
+
+        // C(a, k+1) = C(a, k) * (a-k) / (k+1)
+        below = below.checked_mul(index - i)? / (i + 1);
+        // C(a-1, k-1) = C(a, k) * k / a
+        if remaining > 0 {
+            above = above.checked_mul(remaining)? / (index - i - 1);
         }
     }
+
+    Some(total)
 }
 
-#[aoc(day9, part1)]
-pub fn solve_part1(values_changes: &Vec<Vec<i32>>) -> i32 {
-    let mut sum = 0;
-    for changes in values_changes {
-        sum += *changes.last().unwrap();
-        each_diffs(changes, |diffs| sum += diffs.last().unwrap());
+/// The generalised binomial coefficient C(n, k), which works for negative `n` too, or None if it overflows
+fn binomial(n: i128, k: i128) -> Option<i128> {
+    // Each intermediate value is C(n, j), so the division is always exact
+    (0..k).try_fold(1i128, |coefficient, j| {
+        Some(coefficient.checked_mul(n - j)? / (j + 1))
+    })
+}
+
+/// Find the value at `index` of the sequence, which needs at least 2 values to have any differences
+fn extrapolate(values: &[i64], index: i64) -> Result<i64, SequenceErrorKind> {
+    if values.len() < 2 {
+        return Err(SequenceErrorKind::NeverSettles);
     }
-    sum
+
+    value_at(values, index).ok_or(SequenceErrorKind::Overflow)
+}
+
+#[aoc(day9, part1)]
+pub fn solve_part1(values_changes: &[Vec<i64>]) -> Result<i64, SequenceError> {
+    sum_lines(values_changes, |values| {
+        extrapolate(values, values.len() as i64)
+    })
 }
 
 #[aoc(day9, part2)]
-pub fn solve_part2(values_changes: &Vec<Vec<i32>>) -> i32 {
-    let mut start_values: Vec<i32> = Vec::new();
+pub fn solve_part2(values_changes: &[Vec<i64>]) -> Result<i64, SequenceError> {
+    sum_lines(values_changes, |values| extrapolate(values, -1))
+}
 
-    values_changes
-        .iter()
-        .map(|changes| {
-            start_values.push(changes[0]);
-            each_diffs(changes, |diffs| start_values.push(diffs[0]));
-            start_values
-                .drain(..)
-                .rev()
-                .reduce(|diff, start_value| start_value - diff)
-                .unwrap()
-        })
-        .sum()
+/// Builds every layer of differences, useful for checking [solve_part1]
+#[aoc(day9, part1, diffs)]
+pub fn solve_part1_diffs(values_changes: &[Vec<i64>]) -> Result<i64, SequenceError> {
+    sum_lines(values_changes, |changes| {
+        let mut end_values: Vec<i128> = Vec::new();
+        each_diffs(changes, |diffs| end_values.push(diffs[diffs.len() - 1]))?;
+
+        end_values
+            .into_iter()
+            .try_fold(0i128, i128::checked_add)
+            .and_then(|next| next.try_into().ok())
+            .ok_or(SequenceErrorKind::Overflow)
+    })
+}
+
+/// Builds every layer of differences, useful for checking [solve_part2]
+#[aoc(day9, part2, diffs)]
+pub fn solve_part2_diffs(values_changes: &[Vec<i64>]) -> Result<i64, SequenceError> {
+    sum_lines(values_changes, |changes| {
+        let mut start_values: Vec<i128> = Vec::new();
+        each_diffs(changes, |diffs| start_values.push(diffs[0]))?;
+
+        start_values
+            .into_iter()
+            .rev()
+            .try_fold(0i128, |diff, start_value| start_value.checked_sub(diff))
+            .and_then(|previous| previous.try_into().ok())
+            .ok_or(SequenceErrorKind::Overflow)
+    })
+}
+
+/// Fits an exact polynomial to each sequence, useful for checking [solve_part1] and [solve_part2]
+fn sum_polynomial_values(
+    values_changes: &[Vec<i64>],
+    index: impl Fn(&[i64]) -> i64,
+) -> Result<i64, SequenceError> {
+    sum_lines(values_changes, |values| {
+        let polynomial = Polynomial::fit_sequence(values).map_err(|err| match err {
+            FitError::NotPolynomial => SequenceErrorKind::NeverSettles,
+            FitError::Overflow => SequenceErrorKind::Overflow,
+        })?;
+
+        let value = polynomial
+            .evaluate(index(values))
+            .ok_or(SequenceErrorKind::Overflow)?
+            .to_integer()
+            .expect("A polynomial through integers at each integer x has an integer value at each integer x");
+
+        value.try_into().map_err(|_| SequenceErrorKind::Overflow)
+    })
+}
+
+#[aoc(day9, part1, polynomial)]
+pub fn solve_part1_polynomial(values_changes: &[Vec<i64>]) -> Result<i64, SequenceError> {
+    sum_polynomial_values(values_changes, |values| values.len() as i64)
+}
+
+#[aoc(day9, part2, polynomial)]
+pub fn solve_part2_polynomial(values_changes: &[Vec<i64>]) -> Result<i64, SequenceError> {
+    sum_polynomial_values(values_changes, |_| -1)
 }
@@ -0,0 +1,998 @@
+pub mod polynomial;
+
+use std::{
+    borrow::Borrow,
+    collections::HashMap,
+    error::Error,
+    fmt::{self, Debug, Display},
+    hash::Hash,
+    ops::{Add, BitOr, Div, Index, Mul, RangeInclusive, Rem, Shl, Shr, Sub},
+    str::FromStr,
+};
+
+/// Common operations on the primitive integer types, for helpers that need to work with any of them
+pub trait Integer:
+    Copy
+    + Ord
+    + Debug
+    + Display
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+    + BitOr<Output = Self>
+    + Shl<u32, Output = Self>
+    + Shr<u32, Output = Self>
+{
+    /// The unsigned type with the same width, which is Self for the unsigned types
+    type Unsigned: Integer;
+
+    const ZERO: Self;
+    const ONE: Self;
+    const MIN: Self;
+    const MAX: Self;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+    fn trailing_zeros(self) -> u32;
+
+    /// The distance between the two values, which always fits in a u128, even when it doesn't fit in Self
+    fn abs_diff(self, other: Self) -> u128;
+
+    /// The absolute value, which always fits in the unsigned type, even for Self::MIN
+    fn unsigned_abs(self) -> Self::Unsigned;
+
+    /// Convert back from the unsigned type, or None if the value is too large for Self
+    fn try_from_unsigned(value: Self::Unsigned) -> Option<Self>;
+}
+
+macro_rules! impl_integer {
+    ($($int:ty => $unsigned:ty),*) => {
+        $(
+            impl Integer for $int {
+                type Unsigned = $unsigned;
+
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+                const MIN: Self = <$int>::MIN;
+                const MAX: Self = <$int>::MAX;
+
+                fn checked_add(self, rhs: Self) -> Option<Self> {
+                    <$int>::checked_add(self, rhs)
+                }
+
+                fn checked_sub(self, rhs: Self) -> Option<Self> {
+                    <$int>::checked_sub(self, rhs)
+                }
+
+                fn checked_mul(self, rhs: Self) -> Option<Self> {
+                    <$int>::checked_mul(self, rhs)
+                }
+
+                fn trailing_zeros(self) -> u32 {
+                    <$int>::trailing_zeros(self)
+                }
+
+                fn abs_diff(self, other: Self) -> u128 {
+                    <$int>::abs_diff(self, other) as u128
+                }
+
+                fn unsigned_abs(self) -> $unsigned {
+                    <$int>::abs_diff(self, 0)
+                }
+
+                fn try_from_unsigned(value: $unsigned) -> Option<Self> {
+                    <$int>::try_from(value).ok()
+                }
+            }
+        )*
+    };
+}
+
+impl_integer!(
+    u8 => u8,
+    u16 => u16,
+    u32 => u32,
+    u64 => u64,
+    u128 => u128,
+    usize => usize,
+    i8 => u8,
+    i16 => u16,
+    i32 => u32,
+    i64 => u64,
+    i128 => u128,
+    isize => usize
+);
+
+pub fn try_ascii_to_digit(c: u8) -> Option<u32> {
+    let digit = c.wrapping_sub(b'0');
+    if digit > 9 {
+        None
+    } else {
+        Some(digit as u32)
+    }
+}
+
+pub fn ascii_to_digit(c: u8) -> u32 {
+    (c - b'0').into()
+}
+
+pub fn parse_vec<T: FromStr>(input: &str, delimiter: &str) -> Vec<T> {
+    input.split(delimiter).flat_map(str::parse).collect()
+}
+
+pub fn get_bytes_len<const START: usize, const LEN: usize>(input: &str) -> [u8; LEN] {
+    input.as_bytes()[START..(START + LEN)].try_into().unwrap()
+}
+
+/// Calculate the greatest common divisor, which is never negative
+///
+/// Panics if the result doesn't fit in T, which can only happen for `gcd(T::MIN, 0)` and `gcd(T::MIN, T::MIN)` of the signed types
+pub fn gcd<T: Integer>(a: T, b: T) -> T {
+    checked_gcd(a, b).expect("gcd is too large for the integer type")
+}
+
+/// Calculate the greatest common divisor, or None if the result doesn't fit in T
+pub fn checked_gcd<T: Integer>(a: T, b: T) -> Option<T> {
+    T::try_from_unsigned(binary_gcd(a.unsigned_abs(), b.unsigned_abs()))
+}
+
+/// Calculate the greatest common divisor of two unsigned values
+///
+/// Uses [Stein's algorithm](https://en.wikipedia.org/wiki/Binary_GCD_algorithm)
+fn binary_gcd<U: Integer>(mut a: U, mut b: U) -> U {
+    if a == U::ZERO || b == U::ZERO {
+        return a | b;
+    }
+
+    // gcd(2u, 2v) = 2*gcd(u, v)
+    // trailing_zeros effectivelly gives us the greatest power of 2, as an amount to shift
+    let greatest_common_pow2_shift = (a | b).trailing_zeros();
+
+    // gcd(2u, v) = gcd(u, v) if v is odd
+    // gcd(u, 2v) = gcd(u, v) if u is odd
+    // Divide out greatest power of 2 for each to get both to be odd
+    // Includes greatest_common_pow2_shift automatically to make at least one odd before applying the odd vs even rule
+    a = a >> a.trailing_zeros();
+    b = b >> b.trailing_zeros();
+
+    // Both a and b are guaranteed to be odd throughout this loop
+    while a != b {
+        if a < b {
+            // Ensure difference of a and b will be positive
+            std::mem::swap(&mut a, &mut b);
+        }
+
+        // gcd(a, b) = gcd(a-b, b) if both a and b are odd
+        a = a - b;
+
+        // gcd(2u, b) = gcd(u, b) if b is odd
+        // Divide out greatest power of 2 to ensure a is odd
+        a = a >> a.trailing_zeros();
+        // b is already odd
+    }
+
+    a << greatest_common_pow2_shift
+}
+
+/// Calculate the least common multiple, which is never negative
+///
+/// Panics if the result doesn't fit in T
+pub fn lcm<T: Integer>(a: T, b: T) -> T {
+    checked_lcm(a, b).expect("lcm is too large for the integer type")
+}
+
+/// Calculate the least common multiple, or None if the result doesn't fit in T
+pub fn checked_lcm<T: Integer>(a: T, b: T) -> Option<T> {
+    let a = a.unsigned_abs();
+    let b = b.unsigned_abs();
+    if a == T::Unsigned::ZERO || b == T::Unsigned::ZERO {
+        return Some(T::ZERO);
+    }
+
+    // Doing the division before the multiplication avoids overflow in the intermediate calculation
+    // The division is guaranteed to be an integer since the gcd inherently divides b
+    T::try_from_unsigned(a.checked_mul(b / binary_gcd(a, b))?)
+}
+
+/// Calculate the least common multiple of all the values, or None if it doesn't fit in T
+///
+/// The least common multiple of no values is 1
+pub fn checked_lcm_all<T: Integer>(values: impl IntoIterator<Item = T>) -> Option<T> {
+    values
+        .into_iter()
+        .try_fold(T::ONE, |total, value| checked_lcm(total, value))
+}
+
+/// Calculate the greatest common divisor `g` along with `x` and `y` such that `a*x + b*y = g`
+///
+/// Uses the [extended Euclidean algorithm](https://en.wikipedia.org/wiki/Extended_Euclidean_algorithm).
+/// Works in i128 so that the coefficients for any 64 bit inputs fit without overflowing
+pub fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+
+    // Invariants: a*old_x + b*old_y = old_r and a*x + b*y = r
+    while r != 0 {
+        let quotient = old_r / r;
+        (old_r, r) = (r, old_r - quotient * r);
+        (old_x, x) = (x, old_x - quotient * x);
+        (old_y, y) = (y, old_y - quotient * y);
+    }
+
+    // Keep the gcd positive, to match gcd
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// Find `x` in `0..modulus` such that `a*x ≡ 1 (mod modulus)`
+///
+/// Returns None if `a` and `modulus` aren't coprime, since then there is no inverse, or if `modulus` isn't positive
+pub fn mod_inverse(a: i128, modulus: i128) -> Option<i128> {
+    if modulus <= 0 {
+        return None;
+    }
+
+    let (g, x, _) = extended_gcd(a.rem_euclid(modulus), modulus);
+    if g == 1 {
+        Some(x.rem_euclid(modulus))
+    } else {
+        None
+    }
+}
+
+/// Solve a system of congruences `x ≡ remainder (mod modulus)` using the
+/// [Chinese remainder theorem](https://en.wikipedia.org/wiki/Chinese_remainder_theorem)
+///
+/// The moduli don't need to be coprime, and the remainders can be any offset, including negative ones or ones larger than their modulus.
+/// Returns the smallest non-negative solution along with the least common multiple of the moduli, every other solution differs by a multiple of it.
+/// Returns None if the congruences contradict each other, any modulus isn't positive, or the combined modulus doesn't fit in an i128
+pub fn crt(congruences: impl IntoIterator<Item = (i128, i128)>) -> Option<(i128, i128)> {
+    congruences
+        .into_iter()
+        .try_fold((0, 1), |(x, modulus), (remainder, next_modulus)| {
+            if next_modulus <= 0 {
+                return None;
+            }
+            let remainder = remainder.rem_euclid(next_modulus);
+
+            // Need x + modulus*k ≡ remainder (mod next_modulus)
+            // modulus*k ≡ remainder - x (mod next_modulus)
+            let g = gcd(modulus, next_modulus);
+            let diff = remainder - x;
+            if diff % g != 0 {
+                // No k can make up the difference
+                return None;
+            }
+
+            // Divide everything through by g, so that modulus/g is invertible mod next_modulus/g
+            let reduced_modulus = next_modulus / g;
+            let inverse = mod_inverse(modulus / g, reduced_modulus)?;
+            // Both factors are less than reduced_modulus, so this can only overflow for moduli above 2^63
+            let k = ((diff / g).rem_euclid(reduced_modulus))
+                .checked_mul(inverse)?
+                .rem_euclid(reduced_modulus);
+
+            let combined_modulus = modulus.checked_mul(reduced_modulus)?;
+            Some((x + modulus * k, combined_modulus))
+        })
+}
+
+/// Gives each distinct value a dense id, counting up from 0 in the order the values are first interned
+///
+/// `Id` can be made smaller than usize to make tables indexed by the ids more compact
+pub struct Interner<T, Id = usize> {
+    ids: HashMap<T, Id>,
+    values: Vec<T>,
+}
+
+impl<T: Hash + Eq + Clone, Id: Copy + TryFrom<usize> + Into<usize>> Interner<T, Id> {
+    pub fn new() -> Self {
+        Interner {
+            ids: HashMap::new(),
+            values: Vec::new(),
+        }
+    }
+
+    /// Get the id of the value, giving it the next id if it hasn't been seen before
+    ///
+    /// Panics if there are more distinct values than `Id` can represent
+    pub fn intern(&mut self, value: T) -> Id {
+        if let Some(id) = self.ids.get(&value) {
+            return *id;
+        }
+
+        let id = Id::try_from(self.values.len())
+            .unwrap_or_else(|_| panic!("Too many values to intern, ran out of ids"));
+        self.ids.insert(value.clone(), id);
+        self.values.push(value);
+
+        id
+    }
+
+    /// Get the id of a value that has already been interned
+    pub fn get<Q>(&self, value: &Q) -> Option<Id>
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.ids.get(value).copied()
+    }
+
+    /// Get the value that was given the id
+    pub fn value(&self, id: Id) -> &T {
+        &self.values[id.into()]
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Iterate over the ids and their values, in order of id
+    pub fn iter(&self) -> impl Iterator<Item = (Id, &T)> {
+        self.values.iter().enumerate().map(|(index, value)| {
+            // Every index was already converted to an id when the value was interned
+            let id = Id::try_from(index).unwrap_or_else(|_| unreachable!());
+            (id, value)
+        })
+    }
+}
+
+impl<T: Hash + Eq + Clone, Id: Copy + TryFrom<usize> + Into<usize>> Default for Interner<T, Id> {
+    fn default() -> Self {
+        Interner::new()
+    }
+}
+
+/// Simple 2d grid of elements
+///
+/// Doesn't have all the checking that a good implementation should have
+pub struct Vec2d<T> {
+    data: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Vec2d<T> {
+    pub fn new() -> Self {
+        Vec2d {
+            data: Vec::new(),
+            width: 0,
+            height: 0,
+        }
+    }
+
+    pub fn iter(&self) -> Vec2dIter<'_, T> {
+        Vec2dIter {
+            x: 0,
+            y: 0,
+            width: self.width,
+            iter: self.data.iter(),
+        }
+    }
+
+    /// Adds a new row to the [Vec2d<T>]
+    ///
+    /// WARNING: This doesn't check that the iterator produces the correct number of elements
+    pub fn add_row<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.data.extend(iter);
+        if self.width == 0 {
+            self.width = self.data.len();
+        }
+        self.height += 1;
+    }
+
+    pub fn rows(&self) -> std::slice::Chunks<'_, T> {
+        self.data.chunks(self.width)
+    }
+
+    pub fn above_left(&self, x: usize, y: usize) -> Option<&T> {
+        if x == 0 || y == 0 {
+            None
+        } else {
+            Some(&self[(x - 1, y - 1)])
+        }
+    }
+
+    pub fn above(&self, x: usize, y: usize) -> Option<&T> {
+        if y == 0 {
+            None
+        } else {
+            Some(&self[(x, y - 1)])
+        }
+    }
+
+    pub fn above_right(&self, x: usize, y: usize) -> Option<&T> {
+        if x == self.width - 1 || y == 0 {
+            None
+        } else {
+            Some(&self[(x + 1, y - 1)])
+        }
+    }
+
+    pub fn left(&self, x: usize, y: usize) -> Option<&T> {
+        if x == 0 {
+            None
+        } else {
+            Some(&self[(x - 1, y)])
+        }
+    }
+
+    pub fn at(&self, x: usize, y: usize) -> &T {
+        &self[(x, y)]
+    }
+
+    pub fn right(&self, x: usize, y: usize) -> Option<&T> {
+        if x == self.width - 1 {
+            None
+        } else {
+            Some(&self[(x + 1, y)])
+        }
+    }
+
+    pub fn below_left(&self, x: usize, y: usize) -> Option<&T> {
+        if x == 0 || y == self.height - 1 {
+            None
+        } else {
+            Some(&self[(x - 1, y + 1)])
+        }
+    }
+
+    pub fn below(&self, x: usize, y: usize) -> Option<&T> {
+        if y == self.height - 1 {
+            None
+        } else {
+            Some(&self[(x, y + 1)])
+        }
+    }
+
+    pub fn below_right(&self, x: usize, y: usize) -> Option<&T> {
+        if x == self.width - 1 || y == self.height - 1 {
+            None
+        } else {
+            Some(&self[(x + 1, y + 1)])
+        }
+    }
+}
+
+impl<T> Index<(usize, usize)> for Vec2d<T> {
+    type Output = T;
+
+    fn index(&self, index: (usize, usize)) -> &Self::Output {
+        let (x, y) = index;
+
+        &self.data[self.width * y + x]
+    }
+}
+
+impl<T> Default for Vec2d<T> {
+    fn default() -> Self {
+        Vec2d::new()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Vec2d<T> {
+    type Item = (usize, usize, &'a T);
+    type IntoIter = Vec2dIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+pub struct Vec2dIter<'a, T> {
+    x: usize,
+    y: usize,
+    width: usize,
+    iter: std::slice::Iter<'a, T>,
+}
+
+impl<'a, T> Iterator for Vec2dIter<'a, T> {
+    type Item = (usize, usize, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.iter.next()?;
+        let x = self.x;
+        let y = self.y;
+
+        self.x += 1;
+        if self.x == self.width {
+            self.x = 0;
+            self.y += 1;
+        }
+
+        Some((x, y, item))
+    }
+}
+
+/// Reasons a range can't be added to a [RangeMap]
+#[derive(Debug, PartialEq, Eq)]
+pub enum RangeMapError<K> {
+    /// The range's start is after its end, so it contains no keys
+    Empty(RangeInclusive<K>),
+    /// The range shares keys with a range already in the map
+    Overlap {
+        existing: RangeInclusive<K>,
+        new: RangeInclusive<K>,
+    },
+    /// More than one key maps to each of the values in the range, so the map can't be inverted
+    NotInvertible(RangeInclusive<K>),
+}
+
+impl<K: Debug> Display for RangeMapError<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RangeMapError::Empty(range) => write!(f, "range {:?} is empty", range),
+            RangeMapError::Overlap { existing, new } => {
+                write!(f, "range {:?} overlaps range {:?}", new, existing)
+            }
+            RangeMapError::NotInvertible(range) => {
+                write!(f, "values {:?} are mapped to by more than one key", range)
+            }
+        }
+    }
+}
+
+impl<K: Debug> Error for RangeMapError<K> {}
+
+#[derive(Default)]
+pub struct RangeMap<K: Ord + Copy, V> {
+    entries: Vec<(RangeInclusive<K>, V)>,
+}
+
+impl<K: Ord + Copy, V> RangeMap<K, V> {
+    pub fn new() -> Self {
+        RangeMap {
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn from_iter(iter: impl Iterator<Item = (RangeInclusive<K>, V)>) -> Self {
+        let mut map = RangeMap {
+            entries: Vec::from_iter(iter),
+        };
+
+        map.entries
+            .sort_unstable_by_key(|(range, _)| *range.start());
+
+        map
+    }
+
+    /// Like [RangeMap::from_iter], but fails if any of the ranges are empty or overlap each other
+    pub fn try_from_iter(
+        iter: impl Iterator<Item = (RangeInclusive<K>, V)>,
+    ) -> Result<Self, RangeMapError<K>> {
+        let map = RangeMap::from_iter(iter);
+
+        if let Some((range, _)) = map.entries.iter().find(|(range, _)| range.is_empty()) {
+            return Err(RangeMapError::Empty(range.clone()));
+        }
+
+        // The entries are sorted by start, so any overlap will be between neighbouring ranges
+        if let Some(pair) = map
+            .entries
+            .windows(2)
+            .find(|pair| pair[1].0.start() <= pair[0].0.end())
+        {
+            return Err(RangeMapError::Overlap {
+                existing: pair[0].0.clone(),
+                new: pair[1].0.clone(),
+            });
+        }
+
+        Ok(map)
+    }
+
+    /// Add a single range to the map, keeping the ranges sorted
+    ///
+    /// Fails without changing the map if the range is empty or overlaps a range already in the map
+    pub fn insert(&mut self, range: RangeInclusive<K>, value: V) -> Result<(), RangeMapError<K>> {
+        if range.is_empty() {
+            return Err(RangeMapError::Empty(range));
+        }
+
+        let index = self
+            .entries
+            .partition_point(|(existing, _)| existing.start() <= range.start());
+
+        // Only the ranges either side of the insertion point can overlap, since the ranges don't overlap each other
+        let before = index.checked_sub(1).map(|before| &self.entries[before].0);
+        let after = self.entries.get(index).map(|(existing, _)| existing);
+        if let Some(existing) = before
+            .filter(|before| before.end() >= range.start())
+            .or(after.filter(|after| after.start() <= range.end()))
+        {
+            return Err(RangeMapError::Overlap {
+                existing: existing.clone(),
+                new: range,
+            });
+        }
+
+        self.entries.insert(index, (range, value));
+        Ok(())
+    }
+
+    /// Iterate over the ranges and their values, in order of the start of each range
+    pub fn iter(&self) -> impl Iterator<Item = (&RangeInclusive<K>, &V)> {
+        self.entries.iter().map(|(range, value)| (range, value))
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q> + Ord,
+        Q: Ord + ?Sized,
+    {
+        self.get_range(key).map(|(_, value)| value)
+    }
+
+    pub fn get_range<Q>(&self, key: &Q) -> Option<(&RangeInclusive<K>, &V)>
+    where
+        K: Borrow<Q> + Ord,
+        Q: Ord + ?Sized,
+    {
+        // Entries are sorted by start, so this is the number of ranges that start at or before the key
+        let index = self
+            .entries
+            .partition_point(|(range, _)| range.start().borrow() <= key);
+
+        // Only the last range starting at or before the key can contain it
+        let (range, value) = self.entries.get(index.checked_sub(1)?)?;
+        if key <= range.end().borrow() {
+            Some((range, value))
+        } else {
+            // larger than this range, and smaller than the next range
+            None
+        }
+    }
+
+    /// Look up each of the `keys` in a single pass over the ranges
+    ///
+    /// The keys must be sorted in ascending order, otherwise keys smaller than an earlier key may not be found
+    pub fn get_many<'a>(
+        &'a self,
+        keys: impl IntoIterator<Item = K> + 'a,
+    ) -> impl Iterator<Item = Option<(&'a RangeInclusive<K>, &'a V)>> + 'a {
+        let mut entries = self.entries.iter().peekable();
+        keys.into_iter().map(move |key| {
+            // Keys are sorted, so a range that ends before this key can't contain any of the later keys either
+            while entries.next_if(|(range, _)| *range.end() < key).is_some() {}
+
+            entries
+                .peek()
+                .copied()
+                .filter(|(range, _)| *range.start() <= key)
+                .map(|(range, value)| (range, value))
+        })
+    }
+}
+
+impl<T: Ord + Copy> RangeMap<T, T> {
+    pub fn get_or_key<'a, 'b, Q>(&'a self, key: &'b Q) -> &'b Q
+    where
+        'a: 'b,
+        T: Borrow<Q> + Ord,
+        Q: Ord + ?Sized,
+    {
+        self.get(key).map(Borrow::borrow).unwrap_or(key)
+    }
+
+    pub fn get_and_offset_or_key(&self, key: T) -> T
+    where
+        T: Sub<T, Output = T> + Add<T, Output = T>,
+    {
+        match self.get_range(&key) {
+            Some((range, value)) => key - *range.start() + *value,
+            None => key,
+        }
+    }
+
+    /// Get all the ranges that the keys in `range` map to
+    ///
+    /// Parts of `range` covered by the map are offset the same way as [RangeMap::get_and_offset_or_key],
+    /// the gaps between them are passed through unchanged.
+    /// The output ranges are in the order of the keys that produced them, not the order of their values
+    pub fn map_range(&self, range: RangeInclusive<T>) -> Vec<RangeInclusive<T>>
+    where
+        T: Integer,
+    {
+        self.split_range(range)
+            .into_iter()
+            .map(|(piece, mapped)| mapped.unwrap_or(piece))
+            .collect()
+    }
+
+    /// Split `range` wherever it crosses the boundary of a range in the map, pairing each piece with the range it maps to
+    ///
+    /// Pieces in the gaps between the ranges of the map are paired with None
+    fn split_range(
+        &self,
+        range: RangeInclusive<T>,
+    ) -> Vec<(RangeInclusive<T>, Option<RangeInclusive<T>>)>
+    where
+        T: Integer,
+    {
+        let mut pieces = Vec::new();
+        let end = *range.end();
+        // None once the whole range has been split
+        let mut remaining = Some(*range.start()).filter(|start| *start <= end);
+
+        // Skip straight to the first range that could overlap
+        let first = self
+            .entries
+            .partition_point(|(source, _)| *source.end() < *range.start());
+
+        for (source, dest) in &self.entries[first..] {
+            let Some(mut start) = remaining else {
+                break;
+            };
+
+            if *source.end() < start {
+                continue;
+            }
+
+            if *source.start() > end {
+                // All later ranges start after this range ends too
+                break;
+            }
+
+            if start < *source.start() {
+                pieces.push((start..=(*source.start() - T::ONE), None));
+                start = *source.start();
+            }
+
+            let overlap_end = end.min(*source.end());
+            pieces.push((
+                start..=overlap_end,
+                Some((start - *source.start() + *dest)..=(overlap_end - *source.start() + *dest)),
+            ));
+
+            // checked_add avoids overflow when the range ends at the maximum value of T
+            remaining = overlap_end.checked_add(T::ONE).filter(|next| *next <= end);
+        }
+
+        if let Some(start) = remaining {
+            pieces.push((start..=end, None));
+        }
+
+        pieces
+    }
+
+    /// Combine two maps into a single map that gives the same result as looking up a key in `self`, then looking up the result in `next`
+    ///
+    /// Keys not covered by either map are still passed through unchanged, pieces that end up mapping to themselves are left out
+    pub fn then(&self, next: &RangeMap<T, T>) -> RangeMap<T, T>
+    where
+        T: Integer,
+    {
+        let mut entries = Vec::new();
+
+        // Keys covered by self, split by where their values land in next
+        for (source, dest) in &self.entries {
+            let image = *dest..=(*source.end() - *source.start() + *dest);
+            for (piece, mapped) in next.split_range(image) {
+                let mapped = mapped.unwrap_or_else(|| piece.clone());
+                let piece_start = *piece.start() - *dest + *source.start();
+                if piece_start != *mapped.start() {
+                    let piece_end = *piece.end() - *dest + *source.start();
+                    entries.push((piece_start..=piece_end, *mapped.start()));
+                }
+            }
+        }
+
+        // Keys not covered by self are passed straight to next, so keep the parts of next's ranges in the gaps of self
+        for (source, dest) in &next.entries {
+            for gap in self.gaps_in(source.clone()) {
+                let value = *gap.start() - *source.start() + *dest;
+                if *gap.start() != value {
+                    entries.push((gap, value));
+                }
+            }
+        }
+
+        RangeMap::from_iter(entries.into_iter())
+    }
+
+    /// Build the map that undoes this one, so that looking up the result of [RangeMap::get_and_offset_or_key] gives back the original key
+    ///
+    /// Fails if two keys map to the same value, either from ranges whose values overlap,
+    /// or from a range whose values land in a gap that already maps to itself
+    pub fn inverse(&self) -> Result<RangeMap<T, T>, RangeMapError<T>>
+    where
+        T: Integer,
+    {
+        let inverse = RangeMap::try_from_iter(self.entries.iter().map(|(source, dest)| {
+            let dest_end = *source.end() - *source.start() + *dest;
+            (*dest..=dest_end, *source.start())
+        }))
+        .map_err(|err| match err {
+            RangeMapError::Overlap { new, .. } => RangeMapError::NotInvertible(new),
+            err => err,
+        })?;
+
+        // Every range covers as many values as it does keys, so with no overlapping values,
+        // the values are exactly the keys as long as none of the values are in a gap
+        for (values, _) in &inverse.entries {
+            if let Some(gap) = self.gaps_in(values.clone()).into_iter().next() {
+                return Err(RangeMapError::NotInvertible(gap));
+            }
+        }
+
+        Ok(inverse)
+    }
+
+    /// Get the parts of `range` that aren't covered by any range in the map
+    fn gaps_in(&self, range: RangeInclusive<T>) -> Vec<RangeInclusive<T>>
+    where
+        T: Integer,
+    {
+        self.split_range(range)
+            .into_iter()
+            .filter_map(|(piece, mapped)| match mapped {
+                None => Some(piece),
+                Some(_) => None,
+            })
+            .collect()
+    }
+}
+
+/// A set of keys, stored as sorted ranges that neither overlap nor touch each other
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+pub struct RangeSet<K: Ord + Copy> {
+    ranges: Vec<RangeInclusive<K>>,
+}
+
+impl<K: Integer> RangeSet<K> {
+    pub fn new() -> Self {
+        RangeSet { ranges: Vec::new() }
+    }
+
+    /// Build the set from any ranges, merging any that overlap or are next to each other, and dropping empty ones
+    pub fn from_iter(iter: impl Iterator<Item = RangeInclusive<K>>) -> Self {
+        let mut ranges: Vec<RangeInclusive<K>> = iter.filter(|range| !range.is_empty()).collect();
+        ranges.sort_unstable_by_key(|range| *range.start());
+
+        let mut set = RangeSet {
+            ranges: Vec::with_capacity(ranges.len()),
+        };
+        for range in ranges {
+            set.push_sorted(range);
+        }
+
+        set
+    }
+
+    /// Add a range that starts at or after the start of every range in the set, merging it with the last range if they touch
+    fn push_sorted(&mut self, range: RangeInclusive<K>) {
+        if let Some(last) = self.ranges.last_mut() {
+            // No next value means the last range already reaches the maximum value, so the new range must touch it
+            let touches = last
+                .end()
+                .checked_add(K::ONE)
+                .is_none_or(|next| *range.start() <= next);
+            if touches {
+                if range.end() > last.end() {
+                    *last = *last.start()..=*range.end();
+                }
+                return;
+            }
+        }
+
+        self.ranges.push(range);
+    }
+
+    /// Add all the keys in `range` to the set
+    pub fn insert(&mut self, range: RangeInclusive<K>) {
+        *self = self.union(&RangeSet::from_iter(std::iter::once(range)));
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, RangeInclusive<K>> {
+        self.ranges.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The total number of keys in the set, saturating at u128::MAX
+    pub fn len(&self) -> u128 {
+        self.ranges
+            .iter()
+            .map(|range| range.end().abs_diff(*range.start()).saturating_add(1))
+            .fold(0, u128::saturating_add)
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        let index = self.ranges.partition_point(|range| range.start() <= key);
+        index
+            .checked_sub(1)
+            .is_some_and(|index| self.ranges[index].end() >= key)
+    }
+
+    /// All the keys in either set
+    pub fn union(&self, other: &RangeSet<K>) -> RangeSet<K> {
+        RangeSet::from_iter(self.ranges.iter().chain(&other.ranges).cloned())
+    }
+
+    /// All the keys in both sets
+    pub fn intersection(&self, other: &RangeSet<K>) -> RangeSet<K> {
+        let mut result = RangeSet::new();
+        let mut ours = self.ranges.iter().peekable();
+        let mut theirs = other.ranges.iter().peekable();
+
+        while let (Some(a), Some(b)) = (ours.peek(), theirs.peek()) {
+            let start = *a.start().max(b.start());
+            let end = *a.end().min(b.end());
+            if start <= end {
+                result.ranges.push(start..=end);
+            }
+
+            // Whichever range ends first can't overlap anything else in the other set
+            if a.end() < b.end() {
+                ours.next();
+            } else {
+                theirs.next();
+            }
+        }
+
+        result
+    }
+
+    /// All the keys in this set that aren't in `other`
+    pub fn difference(&self, other: &RangeSet<K>) -> RangeSet<K> {
+        let mut result = RangeSet::new();
+        let mut theirs = other.ranges.iter().peekable();
+
+        for range in &self.ranges {
+            // None once the rest of the range has been removed
+            let mut remaining = Some(*range.start());
+
+            // Ranges of other that end before this range can't overlap any later ranges either
+            while theirs.next_if(|b| b.end() < range.start()).is_some() {}
+
+            // Don't consume the ranges, they may overlap the next range too
+            for b in theirs.clone() {
+                let Some(start) = remaining else {
+                    break;
+                };
+
+                if b.start() > range.end() {
+                    break;
+                }
+
+                if start < *b.start() {
+                    result.ranges.push(start..=(*b.start() - K::ONE));
+                }
+
+                remaining = b
+                    .end()
+                    .checked_add(K::ONE)
+                    .filter(|next| next <= range.end());
+            }
+
+            if let Some(start) = remaining {
+                result.ranges.push(start..=*range.end());
+            }
+        }
+
+        result
+    }
+}
+
+impl<'a, K: Integer> IntoIterator for &'a RangeSet<K> {
+    type Item = &'a RangeInclusive<K>;
+    type IntoIter = std::slice::Iter<'a, RangeInclusive<K>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
@@ -0,0 +1,292 @@
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
+
+use super::checked_gcd;
+
+/// An exact fraction, always stored in lowest terms with a positive denominator
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Rational {
+    numerator: i128,
+    denominator: i128,
+}
+
+impl Rational {
+    pub const ZERO: Rational = Rational {
+        numerator: 0,
+        denominator: 1,
+    };
+    pub const ONE: Rational = Rational {
+        numerator: 1,
+        denominator: 1,
+    };
+
+    /// Panics if `denominator` is 0
+    ///
+    /// Returns None if the fraction can't be stored in lowest terms, which can only happen when one of the values is i128::MIN
+    pub fn new(numerator: i128, denominator: i128) -> Option<Self> {
+        assert_ne!(denominator, 0, "Rational with a denominator of 0");
+
+        let divisor = checked_gcd(numerator, denominator)?;
+        let (numerator, denominator) = (numerator / divisor, denominator / divisor);
+        if denominator < 0 {
+            Some(Rational {
+                numerator: numerator.checked_neg()?,
+                denominator: denominator.checked_neg()?,
+            })
+        } else {
+            Some(Rational {
+                numerator,
+                denominator,
+            })
+        }
+    }
+
+    pub fn numerator(&self) -> i128 {
+        self.numerator
+    }
+
+    pub fn denominator(&self) -> i128 {
+        self.denominator
+    }
+
+    /// The value as an integer, or None if it isn't a whole number
+    pub fn to_integer(self) -> Option<i128> {
+        if self.denominator == 1 {
+            Some(self.numerator)
+        } else {
+            None
+        }
+    }
+
+    /// Returns None if the result overflows
+    pub fn checked_add(self, rhs: Rational) -> Option<Rational> {
+        // Only multiply by the part of each denominator that the other doesn't share, to keep the values small
+        let divisor = checked_gcd(self.denominator, rhs.denominator)?;
+        let (self_scale, rhs_scale) = (rhs.denominator / divisor, self.denominator / divisor);
+
+        Rational::new(
+            self.numerator
+                .checked_mul(self_scale)?
+                .checked_add(rhs.numerator.checked_mul(rhs_scale)?)?,
+            self.denominator.checked_mul(self_scale)?,
+        )
+    }
+
+    /// Returns None if the result overflows
+    pub fn checked_sub(self, rhs: Rational) -> Option<Rational> {
+        self.checked_add(rhs.checked_neg()?)
+    }
+
+    /// Returns None if the result overflows
+    pub fn checked_neg(self) -> Option<Rational> {
+        Some(Rational {
+            numerator: self.numerator.checked_neg()?,
+            denominator: self.denominator,
+        })
+    }
+
+    /// Returns None if the result overflows
+    pub fn checked_mul(self, rhs: Rational) -> Option<Rational> {
+        // Cancel out common factors before multiplying, to keep the values small
+        let divisor1 = checked_gcd(self.numerator, rhs.denominator)?;
+        let divisor2 = checked_gcd(rhs.numerator, self.denominator)?;
+
+        Rational::new(
+            (self.numerator / divisor1).checked_mul(rhs.numerator / divisor2)?,
+            (self.denominator / divisor2).checked_mul(rhs.denominator / divisor1)?,
+        )
+    }
+
+    /// Returns None if `rhs` is 0 or the result overflows
+    pub fn checked_div(self, rhs: Rational) -> Option<Rational> {
+        if rhs.numerator == 0 {
+            return None;
+        }
+
+        let reciprocal = Rational::new(rhs.denominator, rhs.numerator)?;
+        self.checked_mul(reciprocal)
+    }
+}
+
+impl From<i128> for Rational {
+    fn from(value: i128) -> Self {
+        Rational {
+            numerator: value,
+            denominator: 1,
+        }
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(value: i64) -> Self {
+        Rational::from(i128::from(value))
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.denominator == 1 {
+            write!(f, "{}", self.numerator)
+        } else {
+            write!(f, "{}/{}", self.numerator, self.denominator)
+        }
+    }
+}
+
+/// Why [Polynomial::fit_sequence] couldn't fit a polynomial
+#[derive(Debug, PartialEq, Eq)]
+pub enum FitError {
+    /// There are fewer than 2 values, so there are no differences to show how the values change
+    NotPolynomial,
+    /// A difference or coefficient doesn't fit in an i128
+    Overflow,
+}
+
+impl Display for FitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FitError::NotPolynomial => write!(f, "the values don't settle into a polynomial"),
+            FitError::Overflow => write!(f, "the values are too large"),
+        }
+    }
+}
+
+impl Error for FitError {}
+
+/// A polynomial with exact rational coefficients
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Polynomial {
+    /// The coefficient of x^i is at index i, with no trailing zeros
+    coefficients: Vec<Rational>,
+}
+
+impl Polynomial {
+    /// Build a polynomial from its coefficients, with the coefficient of x^i at index i
+    pub fn new(mut coefficients: Vec<Rational>) -> Self {
+        while coefficients.last() == Some(&Rational::ZERO) {
+            coefficients.pop();
+        }
+
+        Polynomial { coefficients }
+    }
+
+    /// Find the polynomial of the lowest degree that passes through all the `(x, y)` points
+    ///
+    /// Uses [Newton's divided differences](https://en.wikipedia.org/wiki/Newton_polynomial).
+    /// Returns None if two of the points have the same x, or if any of the calculations overflow
+    pub fn interpolate(points: &[(i64, i64)]) -> Option<Self> {
+        let xs: Vec<Rational> = points.iter().map(|(x, _)| Rational::from(*x)).collect();
+
+        // After round j, differences[i] is the divided difference of points i-j..=i
+        let mut differences: Vec<Rational> =
+            points.iter().map(|(_, y)| Rational::from(*y)).collect();
+        for j in 1..points.len() {
+            for i in (j..points.len()).rev() {
+                let dx = xs[i].checked_sub(xs[i - j])?;
+                differences[i] = differences[i]
+                    .checked_sub(differences[i - 1])?
+                    .checked_div(dx)?;
+            }
+        }
+
+        // Expand the Newton form d0 + (x - x0)(d1 + (x - x1)(d2 + ...)) from the inside out
+        let mut coefficients: Vec<Rational> = Vec::with_capacity(points.len());
+        for (difference, x) in differences.iter().zip(&xs).rev() {
+            multiply_by_linear(&mut coefficients, *x)?;
+            coefficients[0] = coefficients[0].checked_add(*difference)?;
+        }
+
+        Some(Polynomial::new(coefficients))
+    }
+
+    /// Find the polynomial through a sequence of values at x = 0, 1, 2, ...
+    ///
+    /// Fails with [FitError::NotPolynomial] if there are fewer than 2 values.
+    /// Otherwise this is the lowest degree polynomial through all of the values, which has degree at most `values.len() - 1`
+    pub fn fit_sequence(values: &[i64]) -> Result<Self, FitError> {
+        if values.len() < 2 {
+            return Err(FitError::NotPolynomial);
+        }
+
+        // The sequence is a polynomial once a layer of differences is constant, a layer with a single value always is
+        // The first value of each layer is the k-th forward difference at x = 0
+        let mut layer: Vec<i128> = values.iter().map(|value| i128::from(*value)).collect();
+        let mut forward_differences = Vec::new();
+        loop {
+            forward_differences.push(layer[0]);
+            if layer.windows(2).all(|pair| pair[0] == pair[1]) {
+                break;
+            }
+
+            layer = layer
+                .windows(2)
+                .map(|pair| pair[1].checked_sub(pair[0]))
+                .collect::<Option<_>>()
+                .ok_or(FitError::Overflow)?;
+        }
+
+        newton_forward(&forward_differences)
+            .map(Polynomial::new)
+            .ok_or(FitError::Overflow)
+    }
+
+    pub fn coefficients(&self) -> &[Rational] {
+        &self.coefficients
+    }
+
+    /// The highest power of x with a non-zero coefficient, or None for the zero polynomial
+    pub fn degree(&self) -> Option<usize> {
+        self.coefficients.len().checked_sub(1)
+    }
+
+    /// Returns None if any of the calculations overflow
+    pub fn evaluate(&self, x: impl Into<Rational>) -> Option<Rational> {
+        let x = x.into();
+
+        // Horner's method
+        self.coefficients
+            .iter()
+            .rev()
+            .try_fold(Rational::ZERO, |total, coefficient| {
+                total.checked_mul(x)?.checked_add(*coefficient)
+            })
+    }
+}
+
+/// The coefficients of the polynomial with these forward differences at x = 0, or None if any of them overflow
+///
+/// The values are equally spaced, so Newton's forward difference formula only needs integer differences:
+/// f(x) = sum of Δᵏf(0) * C(x, k), where C(x, k) = C(x, k-1) * (x - (k-1)) / k
+fn newton_forward(forward_differences: &[i128]) -> Option<Vec<Rational>> {
+    let mut coefficients: Vec<Rational> = Vec::new();
+    let mut binomial = vec![Rational::ONE];
+    for (k, difference) in (0i128..).zip(forward_differences) {
+        if k > 0 {
+            multiply_by_linear(&mut binomial, Rational::from(k - 1))?;
+            let k = Rational::from(k);
+            for coefficient in &mut binomial {
+                *coefficient = coefficient.checked_div(k)?;
+            }
+        }
+
+        coefficients.resize(binomial.len(), Rational::ZERO);
+        let difference = Rational::from(*difference);
+        for (coefficient, weight) in coefficients.iter_mut().zip(&binomial) {
+            *coefficient = coefficient.checked_add(weight.checked_mul(difference)?)?;
+        }
+    }
+
+    Some(coefficients)
+}
+
+/// Multiply the polynomial with these coefficients by (x - `root`), or None if it overflows
+fn multiply_by_linear(coefficients: &mut Vec<Rational>, root: Rational) -> Option<()> {
+    coefficients.insert(0, Rational::ZERO);
+    for i in 0..coefficients.len() - 1 {
+        coefficients[i] = coefficients[i].checked_sub(root.checked_mul(coefficients[i + 1])?)?;
+    }
+
+    Some(())
+}